@@ -1,13 +1,73 @@
 //! Database connection management
 
 use crate::{ConnectionConfig, Result, ServerError};
+use crate::config::AddressFamily;
+use crate::proxy::{self, Socks5Proxy};
+use sqlx::mysql::{MySqlConnectOptions, MySqlSslMode};
 use sqlx::{MySqlConnection, Connection};
-use tracing::{info, debug};
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use tokio::task::JoinHandle;
+use tracing::{info, debug, warn};
+
+/// MySQL server version, detected once per connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    /// Create a version from its components
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Parse a `VERSION()` string such as `8.0.32` or `5.7.40-log`
+    pub fn parse(version: &str) -> Option<Self> {
+        let numeric = version
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?;
+        let mut parts = numeric.split('.').map(|part| part.parse::<u32>());
+
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+
+        Some(Self { major, minor, patch })
+    }
+
+    /// Return a validation error naming `feature` if this version is older than `minimum`
+    pub fn require(&self, minimum: ServerVersion, feature: &str) -> Result<()> {
+        if *self >= minimum {
+            return Ok(());
+        }
+
+        Err(ServerError::validation_error(
+            format!("{} requires MySQL {} or later", feature, minimum),
+            Some(format!("server version is {}", self))
+        ))
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
 
 /// Database connection manager
 pub struct ConnectionManager {
     config: ConnectionConfig,
     connection: Option<MySqlConnection>,
+    /// Local SOCKS5 tunnel task serving the current connection, if proxied
+    proxy_tunnel: Option<JoinHandle<()>>,
+    /// Server version detected when the connection was established
+    server_version: Option<ServerVersion>,
+    /// Whether the connection has a default database (`DATABASE()` is not NULL)
+    has_default_schema: Option<bool>,
 }
 
 impl ConnectionManager {
@@ -16,6 +76,9 @@ impl ConnectionManager {
         Self { 
             config,
             connection: None,
+            proxy_tunnel: None,
+            server_version: None,
+            has_default_schema: None,
         }
     }
 
@@ -45,9 +108,25 @@ impl ConnectionManager {
         }
 
         // Attempt to establish connection
-        match MySqlConnection::connect(&self.config.database_url).await {
-            Ok(conn) => {
+        let connect_result = match &self.config.socks5_proxy {
+            Some(proxy_address) => {
+                let proxy = Socks5Proxy::parse(proxy_address)?
+                    .with_keepalive(self.config.tcp_keepalive_secs.map(std::time::Duration::from_secs));
+                let options = self.start_proxy_tunnel(&proxy).await?;
+                MySqlConnection::connect_with(&options).await
+            }
+            None if self.config.address_family != AddressFamily::Any => {
+                let options = self.resolve_address_family().await?;
+                MySqlConnection::connect_with(&options).await
+            }
+            None => MySqlConnection::connect(&self.config.database_url).await,
+        };
+
+        match connect_result {
+            Ok(mut conn) => {
                 info!("Successfully connected to MySQL database");
+                self.server_version = Self::detect_server_version(&mut conn).await;
+                self.has_default_schema = Self::detect_default_schema(&mut conn).await;
                 self.connection = Some(conn);
                 Ok(())
             }
@@ -64,6 +143,133 @@ impl ConnectionManager {
         }
     }
 
+    /// Resolve the host to an address of the configured family and connect options for it
+    ///
+    /// Unix sockets and hosts given as IP addresses are used as configured.
+    async fn resolve_address_family(&self) -> Result<MySqlConnectOptions> {
+        let options = MySqlConnectOptions::from_str(&self.config.database_url)
+            .map_err(|e| ServerError::connection_error(e, false))?;
+        if options.get_socket().is_some() || options.get_host().parse::<std::net::IpAddr>().is_ok() {
+            return Ok(options);
+        }
+
+        if verifies_host_identity(&options) {
+            return Err(ServerError::configuration_error(
+                "address_family".to_string(),
+                "ssl-mode=VERIFY_IDENTITY needs the host name, which a pinned address family replaces with an IP address".to_string()
+            ));
+        }
+
+        let family = self.config.address_family;
+        let resolved = tokio::net::lookup_host((options.get_host(), options.get_port())).await
+            .map_err(|e| ServerError::connection_error(sqlx::Error::Io(e), true))?;
+        let address = select_address(resolved, family).ok_or_else(|| {
+            ServerError::configuration_error(
+                "address_family".to_string(),
+                format!("Host {} has no {:?} address", options.get_host(), family)
+            )
+        })?;
+
+        debug!("Resolved {} to {} for address family {:?}", options.get_host(), address.ip(), family);
+        Ok(options.host(&address.ip().to_string()))
+    }
+
+    /// Start a SOCKS5 tunnel and return connect options pointing at it
+    ///
+    /// Unix socket connections cannot be proxied and are used as configured.
+    async fn start_proxy_tunnel(&mut self, proxy: &Socks5Proxy) -> Result<MySqlConnectOptions> {
+        let options = MySqlConnectOptions::from_str(&self.config.database_url)
+            .map_err(|e| ServerError::connection_error(e, false))?;
+
+        if options.get_socket().is_some() {
+            warn!("SOCKS5 proxy is ignored for unix socket connections");
+            return Ok(options);
+        }
+
+        if verifies_host_identity(&options) {
+            return Err(ServerError::configuration_error(
+                "socks5_proxy".to_string(),
+                "ssl-mode=VERIFY_IDENTITY needs the host name, which a proxied connection replaces with the local tunnel address".to_string()
+            ));
+        }
+
+        info!("Connecting through SOCKS5 proxy {}:{}", proxy.host, proxy.port);
+        let (tunnel_addr, tunnel) = proxy.start_tunnel(options.get_host(), options.get_port()).await?;
+        if let Some(previous) = self.proxy_tunnel.replace(tunnel) {
+            previous.abort();
+        }
+
+        Ok(proxy::route_through_tunnel(options, tunnel_addr))
+    }
+
+    /// Query the server version, logging rather than failing if it cannot be determined
+    async fn detect_server_version(conn: &mut MySqlConnection) -> Option<ServerVersion> {
+        match sqlx::query_scalar::<_, String>("SELECT VERSION()").fetch_one(conn).await {
+            Ok(version) => {
+                let parsed = ServerVersion::parse(&version);
+                match parsed {
+                    Some(parsed) => info!("MySQL server version: {}", parsed),
+                    None => warn!("Unrecognized MySQL server version: {}", version),
+                }
+                parsed
+            }
+            Err(e) => {
+                warn!("Failed to detect MySQL server version: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Check whether the connection selected a default database
+    async fn detect_default_schema(conn: &mut MySqlConnection) -> Option<bool> {
+        match sqlx::query_scalar::<_, Option<String>>("SELECT DATABASE()").fetch_one(conn).await {
+            Ok(database) => {
+                if database.is_none() {
+                    warn!("Connected without a default database; tools need an explicit 'database' argument");
+                }
+                Some(database.is_some())
+            }
+            Err(e) => {
+                warn!("Failed to detect default database: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether the current connection has a default database, if known
+    pub fn has_default_schema(&self) -> Option<bool> {
+        self.has_default_schema
+    }
+
+    /// Fail with guidance when a query relies on `DATABASE()` but none is selected
+    ///
+    /// Passes when this is unknown so the server itself decides.
+    pub fn require_default_schema(&self) -> Result<()> {
+        if self.has_default_schema == Some(false) {
+            return Err(ServerError::validation_error(
+                "No default database; please specify 'database'".to_string(),
+                Some("the connection URL does not select a database, so DATABASE() is NULL".to_string())
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Server version of the current connection, if known
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        self.server_version
+    }
+
+    /// Check that the server supports `feature`, which needs at least `minimum`
+    ///
+    /// Passes when the version is unknown so the server itself decides.
+    pub fn require_version(&self, minimum: ServerVersion, feature: &str) -> Result<()> {
+        match self.server_version {
+            Some(version) => version.require(minimum, feature),
+            None => Ok(()),
+        }
+    }
+
     /// Check if the connection is established
     pub fn is_connected(&self) -> bool {
         self.connection.is_some()
@@ -91,7 +297,10 @@ impl ConnectionManager {
 
     /// Close the database connection
     pub async fn disconnect(&mut self) -> Result<()> {
-        if let Some(conn) = self.connection.take() {
+        let tunnel = self.proxy_tunnel.take();
+        self.server_version = None;
+        self.has_default_schema = None;
+        let result = if let Some(conn) = self.connection.take() {
             info!("Closing database connection");
             match conn.close().await {
                 Ok(_) => {
@@ -105,7 +314,28 @@ impl ConnectionManager {
         } else {
             debug!("No connection to close");
             Ok(())
+        };
+
+        if let Some(tunnel) = tunnel {
+            tunnel.abort();
         }
+
+        result
+    }
+
+    /// Drop the current connection without closing it gracefully and connect again
+    ///
+    /// Used when the connection is known to be dead.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        info!("Reconnecting to MySQL database");
+        self.connection = None;
+        self.server_version = None;
+        self.has_default_schema = None;
+        if let Some(tunnel) = self.proxy_tunnel.take() {
+            tunnel.abort();
+        }
+
+        self.connect().await
     }
 
     /// Test the connection by executing a simple query
@@ -126,6 +356,20 @@ impl ConnectionManager {
     }
 }
 
+/// Whether the connection checks the server certificate against the host name
+///
+/// Connections that reach the server by another address cannot do that, so
+/// `ssl-mode=VERIFY_IDENTITY` rules out `socks5_proxy`, and `address_family`
+/// for host names.
+pub fn verifies_host_identity(options: &MySqlConnectOptions) -> bool {
+    matches!(options.get_ssl_mode(), MySqlSslMode::VerifyIdentity)
+}
+
+/// Pick the first resolved address of the wanted family
+pub fn select_address(addresses: impl IntoIterator<Item = SocketAddr>, family: AddressFamily) -> Option<SocketAddr> {
+    addresses.into_iter().find(|address| family.matches(address))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,7 +395,7 @@ mod tests {
                     username, password, host, port, database
                 );
                 
-                let config = ConnectionConfig { database_url };
+                let config = ConnectionConfig { database_url, ..Default::default() };
                 let mut manager = ConnectionManager::new(config);
                 
                 // The property we're testing: for any valid connection configuration,
@@ -206,7 +450,7 @@ mod tests {
         ) {
             let rt = tokio::runtime::Runtime::new().unwrap();
             let _ = rt.block_on(async {
-                let config = ConnectionConfig { database_url: invalid_url };
+                let config = ConnectionConfig { database_url: invalid_url, ..Default::default() };
                 let mut manager = ConnectionManager::new(config);
                 
                 // For invalid connection strings, the system should validate
@@ -241,7 +485,7 @@ mod tests {
     // Property test for empty database URL handling
     #[tokio::test]
     async fn test_empty_database_url() {
-        let config = ConnectionConfig { database_url: String::new() };
+        let config = ConnectionConfig { database_url: String::new(), ..Default::default() };
         let mut manager = ConnectionManager::new(config);
         
         match manager.connect().await {
@@ -252,4 +496,57 @@ mod tests {
             _ => panic!("Expected validation error for empty database URL"),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_server_version_parsing_and_gating() {
+        let mysql57 = ServerVersion::parse("5.7.40-log").unwrap();
+        let mysql80 = ServerVersion::parse("8.0.32").unwrap();
+        assert_eq!(mysql57, ServerVersion::new(5, 7, 40));
+        assert_eq!(mysql80, ServerVersion::new(8, 0, 32));
+        assert_eq!(ServerVersion::parse("8.0.35-0ubuntu0.22.04.1"), Some(ServerVersion::new(8, 0, 35)));
+        assert_eq!(ServerVersion::parse("not a version"), None);
+
+        let minimum = ServerVersion::new(8, 0, 18);
+        assert!(mysql80.require(minimum, "EXPLAIN ANALYZE").is_ok());
+        match mysql57.require(minimum, "EXPLAIN ANALYZE") {
+            Err(ServerError::Validation { message, .. }) => {
+                assert_eq!(message, "EXPLAIN ANALYZE requires MySQL 8.0.18 or later");
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_default_schema_gives_guidance() {
+        let mut manager = ConnectionManager::new(ConnectionConfig::default());
+
+        // Unknown until a connection has been made
+        assert_eq!(manager.has_default_schema(), None);
+        assert!(manager.require_default_schema().is_ok());
+
+        manager.has_default_schema = Some(true);
+        assert!(manager.require_default_schema().is_ok());
+
+        manager.has_default_schema = Some(false);
+        match manager.require_default_schema() {
+            Err(ServerError::Validation { message, .. }) => {
+                assert_eq!(message, "No default database; please specify 'database'");
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_select_address_by_family() {
+        let resolved: Vec<SocketAddr> = vec![
+            "[2001:db8::10]:3306".parse().unwrap(),
+            "192.0.2.10:3306".parse().unwrap(),
+            "[2001:db8::11]:3306".parse().unwrap(),
+        ];
+
+        assert_eq!(select_address(resolved.clone(), AddressFamily::Any), Some(resolved[0]));
+        assert_eq!(select_address(resolved.clone(), AddressFamily::Ipv4), Some(resolved[1]));
+        assert_eq!(select_address(resolved.clone(), AddressFamily::Ipv6), Some(resolved[0]));
+        assert_eq!(select_address(resolved[1..2].to_vec(), AddressFamily::Ipv6), None);
+    }
+}
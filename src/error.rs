@@ -206,6 +206,41 @@ impl ServerError {
         }
     }
 
+    /// Check if this error means the database connection is gone
+    ///
+    /// Covers "server has gone away" (2006), "lost connection" (2013),
+    /// idle disconnects (4031) and sockets closed underneath the driver.
+    pub fn is_connection_lost(&self) -> bool {
+        match self {
+            ServerError::Connection { source, .. } | ServerError::Query { source, .. } => {
+                Self::is_connection_lost_source(source)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_connection_lost_source(source: &sqlx::Error) -> bool {
+        match source {
+            sqlx::Error::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::UnexpectedEof
+                    | std::io::ErrorKind::NotConnected
+            ),
+            sqlx::Error::Database(db) => db
+                .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                .is_some_and(|e| Self::is_connection_lost_code(e.number())),
+            _ => false,
+        }
+    }
+
+    /// MySQL error codes reporting a lost connection
+    pub fn is_connection_lost_code(code: u16) -> bool {
+        matches!(code, 2006 | 2013 | 4031)
+    }
+
     /// Get a user-friendly error message (safe to send to clients)
     pub fn user_message(&self) -> String {
         match self {
@@ -303,6 +338,10 @@ impl ServerError {
 
     /// Extract MySQL error code from sqlx error if available
     fn extract_mysql_error_code(source: &sqlx::Error) -> Option<String> {
+        if let Some(code) = Self::database_error_codes(source).and_then(|(code, _)| code) {
+            return Some(code.to_string());
+        }
+
         // Try to extract MySQL error code from the error message
         let error_str = source.to_string();
         
@@ -325,6 +364,40 @@ impl ServerError {
         None
     }
 
+    /// MySQL error number and SQLSTATE of a database error
+    fn database_error_codes(source: &sqlx::Error) -> Option<(Option<u16>, Option<String>)> {
+        let sqlx::Error::Database(db) = source else {
+            return None;
+        };
+
+        // MySQL errors display as "1146 (42S02): message"
+        let mysql_code = db
+            .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+            .map(|e| e.number())
+            .or_else(|| {
+                let display = db.to_string();
+                let digits: String = display.chars().take_while(|c| c.is_ascii_digit()).collect();
+                digits.parse().ok()
+            });
+        let sqlstate = db.code().map(|code| code.into_owned());
+
+        Some((mysql_code, sqlstate))
+    }
+
+    /// Structured error details for clients: the MySQL error code and SQLSTATE
+    pub fn error_data(&self) -> Option<serde_json::Value> {
+        let source = match self {
+            ServerError::Connection { source, .. } | ServerError::Query { source, .. } => source,
+            _ => return None,
+        };
+        let (mysql_code, sqlstate) = Self::database_error_codes(source)?;
+
+        Some(serde_json::json!({
+            "mysql_code": mysql_code,
+            "sqlstate": sqlstate,
+        }))
+    }
+
     /// Sanitize database error messages to remove sensitive information
     fn sanitize_database_error(source: &sqlx::Error) -> String {
         let error_str = source.to_string();
@@ -386,4 +459,79 @@ impl From<std::io::Error> for ServerError {
     fn from(err: std::io::Error) -> Self {
         ServerError::io_error(err, "unknown context".to_string())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_lost_detection() {
+        for code in [2006, 2013, 4031] {
+            assert!(ServerError::is_connection_lost_code(code), "{} should trigger a reconnect", code);
+        }
+        for code in [1045, 1064, 1146] {
+            assert!(!ServerError::is_connection_lost_code(code), "{} should not trigger a reconnect", code);
+        }
+
+        let broken_pipe = sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert!(ServerError::query_error("SELECT 1".to_string(), broken_pipe).is_connection_lost());
+
+        let denied = sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(!ServerError::query_error("SELECT 1".to_string(), denied).is_connection_lost());
+        assert!(!ServerError::query_error("SELECT 1".to_string(), sqlx::Error::RowNotFound).is_connection_lost());
+        assert!(!ServerError::validation_error("bad".to_string(), None).is_connection_lost());
+    }
+
+    #[derive(Debug)]
+    struct FakeMySqlError;
+
+    impl fmt::Display for FakeMySqlError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "1146 (42S02): Table 'app.missing' doesn't exist")
+        }
+    }
+
+    impl std::error::Error for FakeMySqlError {}
+
+    impl sqlx::error::DatabaseError for FakeMySqlError {
+        fn message(&self) -> &str {
+            "Table 'app.missing' doesn't exist"
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            Some("42S02".into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn test_database_error_exposes_code_and_sqlstate() {
+        let error = ServerError::query_error(
+            "SELECT * FROM missing".to_string(),
+            sqlx::Error::Database(Box::new(FakeMySqlError))
+        );
+
+        assert_eq!(error.error_data(), Some(serde_json::json!({"mysql_code": 1146, "sqlstate": "42S02"})));
+        match &error {
+            ServerError::Query { error_code, .. } => assert_eq!(error_code.as_deref(), Some("1146")),
+            other => panic!("expected query error, got {:?}", other),
+        }
+
+        assert_eq!(ServerError::validation_error("bad".to_string(), None).error_data(), None);
+    }
+}
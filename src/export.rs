@@ -0,0 +1,372 @@
+//! Exporting query results to an HTTP destination
+
+use serde::{Deserialize, Serialize};
+use crate::query::{ColumnInfo, Row};
+use crate::streaming::StreamingConfig;
+use crate::{Result, ServerError};
+use serde_json::Value;
+use std::time::Instant;
+use tokio_stream::{Stream, StreamExt};
+use tracing::info;
+use warp::hyper::{self, body::Sender, Body, Method, Request, Uri};
+
+/// Encoding of exported rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line, keyed by column name
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// Parse a format name (`csv` or `ndjson`)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "ndjson" => Ok(Self::Ndjson),
+            _ => Err(ServerError::validation_error(
+                format!("Unsupported export format: {}", name),
+                Some("Use 'csv' or 'ndjson'".to_string())
+            )),
+        }
+    }
+
+    /// Content type sent with the export
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// Encode the CSV header row; NDJSON has none
+    pub fn encode_header(&self, columns: &[ColumnInfo]) -> Vec<u8> {
+        match self {
+            Self::Csv => {
+                let names: Vec<String> = columns.iter().map(|c| csv_field(&c.name)).collect();
+                format!("{}\n", names.join(",")).into_bytes()
+            }
+            Self::Ndjson => Vec::new(),
+        }
+    }
+
+    /// Encode rows, one line each
+    pub fn encode_rows(&self, columns: &[ColumnInfo], rows: &[Row]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for row in rows {
+            let line = match self {
+                Self::Csv => row.values.iter().map(csv_value).collect::<Vec<_>>().join(","),
+                Self::Ndjson => {
+                    let object: serde_json::Map<String, Value> = columns.iter()
+                        .zip(&row.values)
+                        .map(|(column, value)| (column.name.clone(), value.clone()))
+                        .collect();
+                    Value::Object(object).to_string()
+                }
+            };
+            out.extend_from_slice(line.as_bytes());
+            out.push(b'\n');
+        }
+        out
+    }
+}
+
+/// Format a JSON value as a CSV field; NULL becomes an empty field
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => csv_field(s),
+        other => csv_field(&other.to_string()),
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Summary of a completed export
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportSummary {
+    /// Number of rows sent
+    pub rows_exported: u64,
+    /// Number of body bytes sent
+    pub bytes: u64,
+    /// Time taken to deliver the export
+    pub duration_ms: u64,
+    /// HTTP status returned by the destination
+    pub status: u16,
+    /// Whether `max_total_rows` stopped the export before the last row
+    pub truncated: bool,
+}
+
+/// Parse a destination URL and check its scheme against `allowed_schemes`
+pub fn check_destination(url: &str, allowed_schemes: &[String]) -> Result<Uri> {
+    let uri: Uri = url.parse().map_err(|e: hyper::http::uri::InvalidUri| {
+        ServerError::validation_error("Invalid destination_url".to_string(), Some(e.to_string()))
+    })?;
+
+    let scheme = uri.scheme_str().unwrap_or_default();
+    if uri.host().is_none() || !allowed_schemes.iter().any(|allowed| allowed.eq_ignore_ascii_case(scheme)) {
+        return Err(ServerError::validation_error(
+            format!("Export destination scheme '{}' is not allowed", scheme),
+            Some(format!("Allowed schemes: {}", allowed_schemes.join(", ")))
+        ));
+    }
+
+    Ok(uri)
+}
+
+/// Send rows to `destination` as a chunked HTTP body while they are read
+///
+/// Rows are pulled from `rows` and encoded `rows_per_chunk` at a time, so
+/// the result is never held in memory as a whole. At most `max_total_rows`
+/// rows are sent. A failing row stream aborts the upload, and the
+/// destination must answer with a 2xx status.
+pub async fn export_rows<S>(
+    destination: Uri,
+    method: Method,
+    format: ExportFormat,
+    columns: &[ColumnInfo],
+    rows: S,
+    config: &StreamingConfig,
+) -> Result<ExportSummary>
+where
+    S: Stream<Item = Result<Row>> + Unpin,
+{
+    let start_time = Instant::now();
+    let target = format!("{}://{}", destination.scheme_str().unwrap_or_default(), destination.host().unwrap_or_default());
+    info!("Exporting rows to {}", target);
+
+    let (sender, body) = Body::channel();
+    let request = Request::builder()
+        .method(method)
+        .uri(destination)
+        .header(hyper::header::CONTENT_TYPE, format.content_type())
+        .body(body)
+        .map_err(|e| ServerError::internal_error("Failed to build export request".to_string(), Some(e.to_string())))?;
+
+    let connector = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_or_http()
+        .enable_http1()
+        .build();
+    let client = hyper::Client::builder().build::<_, Body>(connector);
+
+    let mut writer = BodyWriter { sender: Some(sender), bytes: 0 };
+    let (response, fed) = futures::join!(
+        client.request(request),
+        writer.feed(format, columns, rows, config)
+    );
+    let (rows_exported, truncated) = fed?;
+    let response = response
+        .map_err(|e| ServerError::internal_error(format!("Export to {} failed", target), Some(e.to_string())))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ServerError::internal_error(
+            format!("Export destination {} responded with status {}", target, status),
+            None
+        ));
+    }
+
+    Ok(ExportSummary {
+        rows_exported,
+        bytes: writer.bytes,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        status: status.as_u16(),
+        truncated,
+    })
+}
+
+/// Writes encoded rows into an export request body
+struct BodyWriter {
+    sender: Option<Sender>,
+    bytes: u64,
+}
+
+impl BodyWriter {
+    /// Encode and send all rows, returning the number sent and whether the cap cut them off
+    ///
+    /// The body is aborted rather than ended when `rows` fails, so the
+    /// destination never sees a partial export as complete.
+    async fn feed<S>(&mut self, format: ExportFormat, columns: &[ColumnInfo], mut rows: S, config: &StreamingConfig) -> Result<(u64, bool)>
+    where
+        S: Stream<Item = Result<Row>> + Unpin,
+    {
+        let result = self.feed_rows(format, columns, &mut rows, config).await;
+        if let (Err(_), Some(sender)) = (&result, self.sender.take()) {
+            sender.abort();
+        }
+        result
+    }
+
+    async fn feed_rows<S>(&mut self, format: ExportFormat, columns: &[ColumnInfo], rows: &mut S, config: &StreamingConfig) -> Result<(u64, bool)>
+    where
+        S: Stream<Item = Result<Row>> + Unpin,
+    {
+        let rows_per_chunk = config.rows_per_chunk.max(1);
+        let mut rows_exported = 0u64;
+        let mut batch = Vec::with_capacity(rows_per_chunk);
+
+        if !self.send(format.encode_header(columns)).await {
+            return Ok((rows_exported, false));
+        }
+        let truncated = loop {
+            if config.max_total_rows.is_some_and(|max| batch.len() as u64 + rows_exported >= max as u64) {
+                break rows.try_next().await?.is_some();
+            }
+            match rows.try_next().await? {
+                Some(row) => batch.push(row),
+                None => break false,
+            }
+            if batch.len() >= rows_per_chunk {
+                if !self.send(format.encode_rows(columns, &batch)).await {
+                    return Ok((rows_exported, false));
+                }
+                rows_exported += batch.len() as u64;
+                batch.clear();
+            }
+        };
+        if self.send(format.encode_rows(columns, &batch)).await {
+            rows_exported += batch.len() as u64;
+        }
+
+        Ok((rows_exported, truncated))
+    }
+
+    /// Send one chunk; false once the destination stopped reading the body
+    async fn send(&mut self, chunk: Vec<u8>) -> bool {
+        let Some(sender) = self.sender.as_mut() else { return false };
+        if chunk.is_empty() {
+            return true;
+        }
+        let len = chunk.len() as u64;
+        if sender.send_data(chunk.into()).await.is_err() {
+            return false;
+        }
+        self.bytes += len;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::QueryResult;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use warp::Filter;
+
+    fn sample_result() -> QueryResult {
+        QueryResult::from_values(&["id", "name"], vec![
+            vec![json!(1), json!("Ada, Countess")],
+            vec![json!(2), json!(null)],
+            vec![json!(3), json!("say \"hi\"")],
+        ])
+    }
+
+    #[test]
+    fn test_check_destination_scheme_allowlist() {
+        let https = vec!["https".to_string()];
+
+        assert!(check_destination("https://bucket.example.com/export?sig=abc", &https).is_ok());
+        assert!(matches!(
+            check_destination("http://bucket.example.com/export", &https),
+            Err(ServerError::Validation { .. })
+        ));
+        assert!(check_destination("file:///etc/passwd", &["file".to_string()]).is_err());
+    }
+
+    /// Content type and body of the last upload a sink received
+    type Received = Arc<Mutex<Option<(String, String)>>>;
+
+    /// Serve a PUT sink on an ephemeral port that records the content type and body
+    fn spawn_sink() -> (String, Received) {
+        let received = Arc::new(Mutex::new(None));
+        let sink = {
+            let received = Arc::clone(&received);
+            warp::put()
+                .and(warp::header::<String>("content-type"))
+                .and(warp::body::bytes())
+                .map(move |content_type: String, body: warp::hyper::body::Bytes| {
+                    *received.lock().unwrap() = Some((content_type, String::from_utf8_lossy(&body).to_string()));
+                    warp::reply()
+                })
+        };
+        let (addr, server) = warp::serve(sink).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        (format!("http://{}/upload", addr), received)
+    }
+
+    async fn export_sample(url: &str, rows: Vec<Result<Row>>, config: &StreamingConfig) -> Result<ExportSummary> {
+        let destination = check_destination(url, &["http".to_string()]).unwrap();
+        let columns = sample_result().columns;
+        export_rows(destination, Method::PUT, ExportFormat::Csv, &columns, tokio_stream::iter(rows), config).await
+    }
+
+    #[tokio::test]
+    async fn test_export_delivers_rows_to_http_sink() {
+        let (url, received) = spawn_sink();
+        let config = StreamingConfig { rows_per_chunk: 2, ..Default::default() };
+
+        let rows = sample_result().rows.into_iter().map(Ok).collect();
+        let summary = export_sample(&url, rows, &config).await.expect("export should succeed");
+
+        let expected = "id,name\n1,\"Ada, Countess\"\n2,\n3,\"say \"\"hi\"\"\"\n";
+        assert_eq!(summary.rows_exported, 3);
+        assert_eq!(summary.bytes, expected.len() as u64);
+        assert_eq!(summary.status, 200);
+        assert!(!summary.truncated);
+        assert_eq!(*received.lock().unwrap(), Some(("text/csv".to_string(), expected.to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_export_stops_at_max_total_rows() {
+        let (url, received) = spawn_sink();
+        let config = StreamingConfig { rows_per_chunk: 1, max_total_rows: Some(2), ..Default::default() };
+
+        let rows = sample_result().rows.into_iter().map(Ok).collect();
+        let summary = export_sample(&url, rows, &config).await.expect("export should succeed");
+
+        assert_eq!(summary.rows_exported, 2);
+        assert!(summary.truncated);
+        assert_eq!(received.lock().unwrap().as_ref().unwrap().1, "id,name\n1,\"Ada, Countess\"\n2,\n");
+
+        // A result that fits exactly is not reported as truncated
+        let rows = sample_result().rows.into_iter().take(2).map(Ok).collect();
+        assert!(!export_sample(&url, rows, &config).await.unwrap().truncated);
+    }
+
+    #[tokio::test]
+    async fn test_failing_rows_abort_the_export() {
+        let (url, received) = spawn_sink();
+        let config = StreamingConfig { rows_per_chunk: 1, ..Default::default() };
+
+        let mut rows: Vec<Result<Row>> = sample_result().rows.into_iter().take(1).map(Ok).collect();
+        rows.push(Err(ServerError::internal_error("row conversion failed".to_string(), None)));
+        let result = export_sample(&url, rows, &config).await;
+
+        assert!(matches!(result, Err(ServerError::Internal { ref message, .. }) if message == "row conversion failed"));
+        assert!(received.lock().unwrap().is_none(), "the sink must not receive a complete body");
+    }
+
+    #[test]
+    fn test_ndjson_rows_are_keyed_by_column() {
+        let result = sample_result();
+
+        let encoded = ExportFormat::Ndjson.encode_rows(&result.columns, &result.rows[..2]);
+
+        assert_eq!(
+            String::from_utf8(encoded).unwrap(),
+            "{\"id\":1,\"name\":\"Ada, Countess\"}\n{\"id\":2,\"name\":null}\n"
+        );
+        assert!(ExportFormat::Ndjson.encode_header(&result.columns).is_empty());
+    }
+}
@@ -3,11 +3,21 @@
 //! A Streamable MySQL MCP (Model Context Protocol) server implementation in Rust
 //! that provides database connectivity and query execution capabilities.
 
+// The tool definitions are one large `json!` literal
+#![recursion_limit = "256"]
+
 pub mod config;
 pub mod connection;
 pub mod query;
 pub mod server;
 pub mod streaming;
+pub mod snapshot;
+pub mod proxy;
+pub mod plan;
+pub mod sql;
+pub mod profile;
+pub mod replication;
+pub mod export;
 pub mod error;
 
 pub use config::{Config, ConnectionConfig, ServerConfig, DatabaseConfig, McpConfig};
@@ -40,11 +40,8 @@ async fn main() -> Result<()> {
     info!("Connection timeout: {}s", config.database.connection_timeout);
     info!("Max connections: {}", config.database.max_connections);
 
-    // Create connection configuration for backward compatibility
-    let connection_config = config.to_connection_config();
-
     // Create the MCP server
-    let server = McpServer::new(connection_config);
+    let server = McpServer::from_config(&config);
 
     // Set up graceful shutdown handling
     let shutdown_signal = setup_shutdown_signal();
@@ -0,0 +1,270 @@
+//! Query plan inspection based on EXPLAIN output
+
+use serde::{Deserialize, Serialize};
+use crate::query::QueryResult;
+
+/// Estimated row count above which a table counts as large for suggestions
+const LARGE_TABLE_ROWS: u64 = 10_000;
+
+/// Rows examined per row returned above which a query counts as unselective
+const UNSELECTIVE_EXAMINED_RATIO: f64 = 100.0;
+
+/// Estimated row combinations above which an unconditioned join is flagged
+const CARTESIAN_JOIN_ROWS: u64 = 1_000_000;
+
+/// One row of EXPLAIN output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlanStep {
+    /// Table accessed in this step
+    pub table: Option<String>,
+    /// Join/access type (ALL, index, range, ref, eq_ref, const, ...)
+    pub access_type: Option<String>,
+    /// Index chosen by the optimizer
+    pub key: Option<String>,
+    /// Estimated number of rows examined
+    pub rows: Option<u64>,
+    /// Additional optimizer information
+    pub extra: Option<String>,
+}
+
+/// Summary of a query plan
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlanSummary {
+    /// Whether any table is read with a full table scan (`type = ALL`)
+    pub full_table_scan: bool,
+    /// Tables read with a full table scan
+    pub full_scan_tables: Vec<String>,
+    /// Indexes used by the plan
+    pub keys_used: Vec<String>,
+    /// Sum of the estimated rows examined over all steps
+    pub estimated_rows_examined: u64,
+    /// Individual plan steps
+    pub steps: Vec<PlanStep>,
+}
+
+impl PlanSummary {
+    /// Build a plan summary from the result of an `EXPLAIN` statement
+    pub fn from_explain(result: &QueryResult) -> Self {
+        let column_index = |name: &str| {
+            result.columns.iter().position(|c| c.name.eq_ignore_ascii_case(name))
+        };
+        let table_col = column_index("table");
+        let type_col = column_index("type");
+        let key_col = column_index("key");
+        let rows_col = column_index("rows");
+        let extra_col = column_index("Extra");
+
+        let steps: Vec<PlanStep> = result.rows
+            .iter()
+            .map(|row| {
+                let text = |index: Option<usize>| {
+                    index
+                        .and_then(|i| row.values.get(i))
+                        .and_then(|v| match v {
+                            serde_json::Value::String(s) => Some(s.clone()),
+                            serde_json::Value::Null => None,
+                            other => Some(other.to_string()),
+                        })
+                };
+                let rows = rows_col
+                    .and_then(|i| row.values.get(i))
+                    .and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.parse().ok())));
+
+                PlanStep {
+                    table: text(table_col),
+                    access_type: text(type_col),
+                    key: text(key_col),
+                    rows,
+                    extra: text(extra_col),
+                }
+            })
+            .collect();
+
+        let full_scan_tables: Vec<String> = steps
+            .iter()
+            .filter(|step| step.access_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("ALL")))
+            .map(|step| step.table.clone().unwrap_or_default())
+            .collect();
+
+        let mut keys_used: Vec<String> = Vec::new();
+        for key in steps.iter().filter_map(|step| step.key.as_ref()) {
+            if !keys_used.contains(key) {
+                keys_used.push(key.clone());
+            }
+        }
+
+        Self {
+            full_table_scan: !full_scan_tables.is_empty(),
+            full_scan_tables,
+            keys_used,
+            estimated_rows_examined: steps.iter().filter_map(|step| step.rows).sum(),
+            steps,
+        }
+    }
+
+    /// Heuristic suggestions for a slow query, given how many rows it returned
+    pub fn suggestions(&self, rows_returned: u64) -> Vec<String> {
+        let mut suggestions = Vec::new();
+
+        for step in &self.steps {
+            let table = step.table.as_deref().unwrap_or("?");
+            let extra = step.extra.as_deref().unwrap_or_default();
+            let full_scan = step.access_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("ALL"));
+
+            if full_scan && step.rows.unwrap_or(0) >= LARGE_TABLE_ROWS {
+                suggestions.push(format!(
+                    "Full table scan on large table '{}' (~{} rows); consider an index on the columns it is filtered or joined on",
+                    table, step.rows.unwrap_or(0)
+                ));
+            }
+            if extra.contains("Using filesort") {
+                suggestions.push(format!(
+                    "Rows of '{}' are sorted with a filesort; an index matching the ORDER BY can avoid it",
+                    table
+                ));
+            }
+            if extra.contains("Using temporary") {
+                suggestions.push(format!(
+                    "A temporary table is built for '{}'; check GROUP BY/DISTINCT columns against the available indexes",
+                    table
+                ));
+            }
+        }
+
+        let ratio = self.rows_examined_per_row_returned(rows_returned);
+        if self.estimated_rows_examined >= LARGE_TABLE_ROWS && ratio >= UNSELECTIVE_EXAMINED_RATIO {
+            suggestions.push(format!(
+                "About {:.0} rows are examined per row returned; a more selective index could reduce the work",
+                ratio
+            ));
+        }
+
+        suggestions
+    }
+
+    /// Joins that look like accidental cartesian products
+    ///
+    /// A table joined with a full scan through the join buffer and without
+    /// any condition (`Using where`) is combined with every row read so far.
+    /// Only joins producing at least `CARTESIAN_JOIN_ROWS` combinations are
+    /// reported.
+    pub fn cartesian_joins(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut rows_so_far: Option<u64> = None;
+
+        for step in &self.steps {
+            let rows = step.rows.unwrap_or(1).max(1);
+            if let Some(previous) = rows_so_far {
+                let extra = step.extra.as_deref().unwrap_or_default();
+                let unconditioned = step.access_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("ALL"))
+                    && step.key.is_none()
+                    && extra.contains("Using join buffer")
+                    && !extra.contains("Using where");
+                let combinations = previous.saturating_mul(rows);
+
+                if unconditioned && combinations >= CARTESIAN_JOIN_ROWS {
+                    warnings.push(format!(
+                        "Table '{}' is joined without a usable key or join condition (~{} x {} rows); check the ON clause",
+                        step.table.as_deref().unwrap_or("?"), previous, rows
+                    ));
+                }
+            }
+            rows_so_far = Some(rows_so_far.map_or(rows, |previous| previous.saturating_mul(rows)));
+        }
+
+        warnings
+    }
+
+    /// Estimated rows examined for each row returned
+    pub fn rows_examined_per_row_returned(&self, rows_returned: u64) -> f64 {
+        self.estimated_rows_examined as f64 / rows_returned.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// (table, type, key, rows, Extra)
+    type ExplainRow<'a> = (&'a str, &'a str, Option<&'a str>, u64, Option<&'a str>);
+
+    /// Build a mocked EXPLAIN result
+    fn explain_result(rows: Vec<ExplainRow>) -> QueryResult {
+        QueryResult::from_values(
+            &["id", "table", "type", "key", "rows", "Extra"],
+            rows.into_iter()
+                .map(|(table, access_type, key, rows, extra)| {
+                    vec![json!(1), json!(table), json!(access_type), json!(key), json!(rows), json!(extra)]
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_detects_full_table_scan() {
+        let summary = PlanSummary::from_explain(&explain_result(vec![
+            ("orders", "ALL", None, 50_000, Some("Using where")),
+            ("customers", "eq_ref", Some("PRIMARY"), 1, None),
+        ]));
+
+        assert!(summary.full_table_scan);
+        assert_eq!(summary.full_scan_tables, vec!["orders".to_string()]);
+        assert_eq!(summary.keys_used, vec!["PRIMARY".to_string()]);
+        assert_eq!(summary.estimated_rows_examined, 50_001);
+    }
+
+    #[test]
+    fn test_suggestions_for_full_scan_plan() {
+        let summary = PlanSummary::from_explain(&explain_result(vec![
+            ("orders", "ALL", None, 50_000, Some("Using where; Using filesort")),
+            ("customers", "eq_ref", Some("PRIMARY"), 1, None),
+        ]));
+
+        let suggestions = summary.suggestions(10);
+
+        assert_eq!(suggestions.len(), 3);
+        assert!(suggestions[0].starts_with("Full table scan on large table 'orders' (~50000 rows)"));
+        assert!(suggestions[1].contains("filesort"));
+        assert!(suggestions[2].starts_with("About 5000 rows are examined per row returned"));
+        assert_eq!(summary.rows_examined_per_row_returned(0), 50_001.0);
+
+        // A selective indexed plan gets no suggestions
+        let indexed = PlanSummary::from_explain(&explain_result(vec![
+            ("orders", "ref", Some("idx_customer"), 12, None),
+        ]));
+        assert!(indexed.suggestions(12).is_empty());
+    }
+
+    #[test]
+    fn test_detects_cartesian_join() {
+        let cross_join = PlanSummary::from_explain(&explain_result(vec![
+            ("orders", "ALL", None, 50_000, None),
+            ("customers", "ALL", None, 2_000, Some("Using join buffer (hash join)")),
+        ]));
+
+        let warnings = cross_join.cartesian_joins();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Table 'customers' is joined without a usable key or join condition (~50000 x 2000 rows)"));
+
+        // A hash join with a condition, an indexed join and a small cross join are fine
+        for steps in [
+            vec![("orders", "ALL", None, 50_000, None), ("customers", "ALL", None, 2_000, Some("Using where; Using join buffer (hash join)"))],
+            vec![("orders", "ALL", None, 50_000, None), ("customers", "eq_ref", Some("PRIMARY"), 1, None)],
+            vec![("sizes", "ALL", None, 5, None), ("colors", "ALL", None, 8, Some("Using join buffer (hash join)"))],
+        ] {
+            assert!(PlanSummary::from_explain(&explain_result(steps)).cartesian_joins().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_indexed_plan_has_no_full_scan() {
+        let summary = PlanSummary::from_explain(&explain_result(vec![
+            ("orders", "ref", Some("idx_customer"), 12, None),
+        ]));
+
+        assert!(!summary.full_table_scan);
+        assert!(summary.full_scan_tables.is_empty());
+        assert_eq!(summary.keys_used, vec!["idx_customer".to_string()]);
+    }
+}
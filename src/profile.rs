@@ -0,0 +1,149 @@
+//! Column statistics computed over fetched query results
+
+use serde::{Deserialize, Serialize};
+use crate::query::QueryResult;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Maximum number of distinct string values tracked per column
+const MAX_DISTINCT_VALUES: usize = 1000;
+
+/// Statistics for one result column
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnProfile {
+    /// Column name
+    pub name: String,
+    /// `numeric`, `string` or `other`, based on the non-null values
+    pub kind: String,
+    /// Number of NULL values
+    pub null_count: usize,
+    /// Minimum value (numeric columns)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Maximum value (numeric columns)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Average value (numeric columns)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg: Option<f64>,
+    /// Number of distinct values (string columns), counted up to a bound
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distinct_count: Option<usize>,
+    /// Whether `distinct_count` stopped at the bound
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub distinct_count_capped: bool,
+}
+
+/// Compute a profile of every column over the rows of a result
+///
+/// DECIMAL values, which are returned as strings to keep their precision,
+/// are profiled as numbers.
+pub fn profile_columns(result: &QueryResult) -> Vec<ColumnProfile> {
+    result.columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let is_decimal = matches!(column.data_type.as_str(), "DECIMAL" | "NUMERIC");
+            let values = result.rows.iter().map(|row| row.values.get(index).unwrap_or(&Value::Null));
+
+            let mut null_count = 0;
+            let mut numbers = Vec::new();
+            let mut strings = HashSet::new();
+            let mut distinct_count_capped = false;
+            let mut other = false;
+
+            for value in values {
+                match value {
+                    Value::Null => null_count += 1,
+                    Value::Number(n) => numbers.extend(n.as_f64()),
+                    Value::String(s) if is_decimal => match s.parse::<f64>() {
+                        Ok(n) => numbers.push(n),
+                        Err(_) => other = true,
+                    },
+                    Value::String(s) => {
+                        if strings.len() < MAX_DISTINCT_VALUES {
+                            strings.insert(s.as_str());
+                        } else if !strings.contains(s.as_str()) {
+                            distinct_count_capped = true;
+                        }
+                    }
+                    _ => other = true,
+                }
+            }
+
+            let non_null = result.rows.len() - null_count;
+            let kind = if other || non_null == 0 {
+                "other"
+            } else if numbers.len() == non_null {
+                "numeric"
+            } else if numbers.is_empty() {
+                "string"
+            } else {
+                // Mixed numbers and strings
+                "other"
+            };
+
+            let mut profile = ColumnProfile {
+                name: column.name.clone(),
+                kind: kind.to_string(),
+                null_count,
+                min: None,
+                max: None,
+                avg: None,
+                distinct_count: None,
+                distinct_count_capped: false,
+            };
+
+            match kind {
+                "numeric" => {
+                    profile.min = numbers.iter().copied().reduce(f64::min);
+                    profile.max = numbers.iter().copied().reduce(f64::max);
+                    profile.avg = Some(numbers.iter().sum::<f64>() / numbers.len() as f64);
+                }
+                "string" => {
+                    profile.distinct_count = Some(strings.len());
+                    profile.distinct_count_capped = distinct_count_capped;
+                }
+                _ => {}
+            }
+
+            profile
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_profile_mixed_type_columns() {
+        let mut result = QueryResult::from_values(&["age", "price", "city", "tags"], vec![
+            vec![json!(30), json!("10.50"), json!("Paris"), json!(["a"])],
+            vec![json!(20), json!("4.50"), json!("Oslo"), json!(null)],
+            vec![json!(null), json!("15.00"), json!("Paris"), json!(["b"])],
+            vec![json!(40), json!(null), json!(null), json!(null)],
+        ]);
+        for (column, data_type) in result.columns.iter_mut().zip(["INT", "DECIMAL", "VARCHAR", "JSON"]) {
+            column.data_type = data_type.to_string();
+        }
+
+        let profiles = profile_columns(&result);
+
+        assert_eq!(profiles[0].kind, "numeric");
+        assert_eq!((profiles[0].min, profiles[0].max, profiles[0].avg), (Some(20.0), Some(40.0), Some(30.0)));
+        assert_eq!(profiles[0].null_count, 1);
+
+        assert_eq!(profiles[1].kind, "numeric");
+        assert_eq!((profiles[1].min, profiles[1].max, profiles[1].avg), (Some(4.5), Some(15.0), Some(10.0)));
+
+        assert_eq!(profiles[2].kind, "string");
+        assert_eq!(profiles[2].distinct_count, Some(2));
+        assert_eq!(profiles[2].null_count, 1);
+        assert!(profiles[2].min.is_none());
+
+        assert_eq!(profiles[3].kind, "other");
+        assert_eq!(profiles[3].null_count, 2);
+    }
+}
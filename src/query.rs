@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::ServerError;
+use crate::config::NonFiniteFloat;
 
 /// Request structure for SQL queries
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +26,9 @@ pub struct QueryResult {
     pub affected_rows: Option<u64>,
     /// Query execution time in milliseconds
     pub execution_time_ms: u64,
+    /// `[row, column]` positions of the values that were truncated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated_cells: Vec<[usize; 2]>,
 }
 
 /// Column metadata information
@@ -36,8 +40,87 @@ pub struct ColumnInfo {
     pub data_type: String,
     /// Whether the column can be null
     pub nullable: bool,
+    /// Encoding applied to the column values (e.g. "base64" for binary columns)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// Whether any value in this column was truncated
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
 
+impl ColumnInfo {
+    /// Build column metadata from a MySQL column
+    pub(crate) fn from_mysql_column(column: &sqlx::mysql::MySqlColumn) -> Self {
+        let data_type = column.type_info().name();
+
+        Self {
+            name: column.name().to_string(),
+            encoding: QueryProcessor::is_binary_type(data_type).then(|| "base64".to_string()),
+            data_type: data_type.to_string(),
+            nullable: true, // MySQL columns are nullable by default unless specified otherwise
+            truncated: false,
+        }
+    }
+}
+
+/// Outcome of the most recently executed query, for diagnostics
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LastQuery {
+    /// Statement fingerprint with literals removed, so no data is kept
+    pub sql_fingerprint: String,
+    /// Whether the query succeeded
+    pub success: bool,
+    /// When the query finished (RFC 3339)
+    pub timestamp: String,
+    /// Time taken, including any reconnect and retry
+    pub duration_ms: u64,
+    /// Error message if the query failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Options controlling how MySQL values are converted to JSON
+#[derive(Debug, Clone)]
+pub struct ValueConversionOptions {
+    /// Maximum number of bytes of a binary value to encode; longer values are truncated
+    pub max_blob_bytes: Option<usize>,
+    /// Maximum number of characters of a text value; longer values are truncated
+    pub max_cell_length: Option<usize>,
+    /// Return zero dates (`0000-00-00`) as NULL rather than as their literal text
+    pub zero_date_as_null: bool,
+    /// How NaN and infinite FLOAT/DOUBLE values are returned
+    pub non_finite_float: NonFiniteFloat,
+    /// Maximum nesting depth of JSON values; deeper arrays and objects are truncated
+    pub max_json_depth: usize,
+}
+
+impl Default for ValueConversionOptions {
+    fn default() -> Self {
+        Self {
+            max_blob_bytes: None,
+            max_cell_length: None,
+            zero_date_as_null: true,
+            non_finite_float: NonFiniteFloat::default(),
+            max_json_depth: 64,
+        }
+    }
+}
+
+impl From<&crate::McpConfig> for ValueConversionOptions {
+    fn from(config: &crate::McpConfig) -> Self {
+        Self {
+            max_blob_bytes: config.max_blob_bytes,
+            max_cell_length: config.max_cell_length,
+            zero_date_as_null: config.zero_date_as_null,
+            non_finite_float: config.non_finite_float,
+            max_json_depth: config.max_json_depth,
+        }
+    }
+}
+
+/// Stands in for JSON nested deeper than `max_json_depth`
+pub const JSON_DEPTH_MARKER: &str = "[truncated: nested too deeply]";
+
 /// A single row of query results
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Row {
@@ -45,12 +128,250 @@ pub struct Row {
     pub values: Vec<serde_json::Value>,
 }
 
+/// Column-oriented view of a query result
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ColumnarResult {
+    /// Column information
+    pub columns: Vec<ColumnInfo>,
+    /// Values keyed by column name, one array entry per row
+    pub data: serde_json::Map<String, serde_json::Value>,
+    /// Number of affected rows (for INSERT/UPDATE/DELETE)
+    pub affected_rows: Option<u64>,
+    /// Query execution time in milliseconds
+    pub execution_time_ms: u64,
+    /// `[row, column]` positions of the values that were truncated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated_cells: Vec<[usize; 2]>,
+}
+
+/// Order two result values for sorting
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value, decimal: bool) -> std::cmp::Ordering {
+    use serde_json::Value;
+
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        _ => 4,
+    };
+    let number = |value: &Value| match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) if decimal => s.parse::<f64>().ok(),
+        _ => None,
+    };
+
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        _ => match (a, b) {
+            (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            _ => rank(a).cmp(&rank(b)).then_with(|| a.to_string().cmp(&b.to_string())),
+        },
+    }
+}
+
+impl QueryResult {
+    /// Remove rows that duplicate an earlier row, keeping the first occurrence
+    ///
+    /// Rows are compared by full-row value equality. Returns the number of
+    /// rows removed.
+    pub fn dedup_rows(&mut self) -> usize {
+        let before = self.rows.len();
+        let mut seen = std::collections::HashSet::new();
+        let mut kept = 0;
+        let new_positions: Vec<Option<usize>> = self.rows
+            .iter()
+            .map(|row| {
+                seen.insert(serde_json::to_string(&row.values).unwrap_or_default()).then(|| {
+                    kept += 1;
+                    kept - 1
+                })
+            })
+            .collect();
+
+        let mut positions = new_positions.iter();
+        self.rows.retain(|_| positions.next().is_some_and(Option::is_some));
+        self.move_truncated_rows(|old| new_positions.get(old).copied().flatten());
+        before - self.rows.len()
+    }
+
+    /// Renumber the rows of `truncated_cells`, dropping cells whose row is gone
+    fn move_truncated_rows(&mut self, new_index: impl Fn(usize) -> Option<usize>) {
+        self.truncated_cells = std::mem::take(&mut self.truncated_cells)
+            .into_iter()
+            .filter_map(|[row, column]| new_index(row).map(|row| [row, column]))
+            .collect();
+        self.truncated_cells.sort_unstable();
+    }
+
+    /// Reorder columns, and the values of every row, to follow `order`
+    ///
+    /// Columns not listed keep their original relative order after the listed
+    /// ones. Naming a column that is not in the result is an error.
+    pub fn reorder_columns(&mut self, order: &[String]) -> Result<()> {
+        let mut indices = Vec::with_capacity(self.columns.len());
+        for name in order {
+            let index = self.columns.iter().position(|c| &c.name == name).ok_or_else(|| {
+                ServerError::validation_error(
+                    format!("Column '{}' in column_order is not in the result", name),
+                    Some(format!("Result columns: {}", self.columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")))
+                )
+            })?;
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+        indices.extend((0..self.columns.len()).filter(|index| !indices.contains(index)).collect::<Vec<_>>());
+
+        for cell in &mut self.truncated_cells {
+            if let Some(position) = indices.iter().position(|&index| index == cell[1]) {
+                cell[1] = position;
+            }
+        }
+
+        let mut columns: Vec<Option<ColumnInfo>> = std::mem::take(&mut self.columns).into_iter().map(Some).collect();
+        self.columns = indices.iter().filter_map(|&index| columns[index].take()).collect();
+        for row in &mut self.rows {
+            let mut values = std::mem::take(&mut row.values);
+            row.values = indices
+                .iter()
+                .map(|&index| values.get_mut(index).map(std::mem::take).unwrap_or(serde_json::Value::Null))
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    /// Sort the rows by the values of one column
+    ///
+    /// NULLs sort first (last when descending), then booleans, numbers and
+    /// strings. DECIMAL values, returned as strings, compare as numbers; dates
+    /// and times compare correctly as their ISO text. The sort is stable.
+    pub fn sort_by_column(&mut self, column: &str, descending: bool) -> Result<()> {
+        let index = self.columns.iter().position(|c| c.name == column).ok_or_else(|| {
+            ServerError::validation_error(
+                format!("Column '{}' in sort_by is not in the result", column),
+                Some(format!("Result columns: {}", self.columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")))
+            )
+        })?;
+        let decimal = matches!(self.columns[index].data_type.as_str(), "DECIMAL" | "NUMERIC");
+
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        order.sort_by(|&a, &b| {
+            let ordering = compare_values(
+                self.rows[a].values.get(index).unwrap_or(&serde_json::Value::Null),
+                self.rows[b].values.get(index).unwrap_or(&serde_json::Value::Null),
+                decimal,
+            );
+            if descending { ordering.reverse() } else { ordering }
+        });
+
+        let mut rows: Vec<Option<Row>> = std::mem::take(&mut self.rows).into_iter().map(Some).collect();
+        self.rows = order.iter().filter_map(|&old| rows[old].take()).collect();
+        let mut new_positions = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_positions[old] = new;
+        }
+        self.move_truncated_rows(|old| new_positions.get(old).copied());
+
+        Ok(())
+    }
+
+    /// Stable hash of the result's shape, as 16 hex digits
+    ///
+    /// Covers the ordered `(name, data_type, nullable)` of every column, so it
+    /// changes when a column is added, removed, reordered, renamed or retyped,
+    /// and is the same across runs and platforms.
+    pub fn schema_hash(&self) -> String {
+        let shape: Vec<(&str, &str, bool)> = self.columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str(), c.nullable))
+            .collect();
+        let canonical = serde_json::to_string(&shape).unwrap_or_default();
+        format!("{:016x}", crate::snapshot::fnv1a_64(canonical.as_bytes()))
+    }
+
+    /// Transpose the row-oriented result into per-column value arrays
+    ///
+    /// NULLs and value types are preserved as-is. If several columns share a
+    /// name, the last one wins.
+    pub fn into_columnar(self) -> ColumnarResult {
+        let mut column_values: Vec<Vec<serde_json::Value>> = self.columns
+            .iter()
+            .map(|_| Vec::with_capacity(self.rows.len()))
+            .collect();
+
+        for row in self.rows {
+            for (index, value) in row.values.into_iter().enumerate() {
+                if let Some(values) = column_values.get_mut(index) {
+                    values.push(value);
+                }
+            }
+        }
+
+        let mut data = serde_json::Map::new();
+        for (column, values) in self.columns.iter().zip(column_values) {
+            data.insert(column.name.clone(), serde_json::Value::Array(values));
+        }
+
+        ColumnarResult {
+            columns: self.columns,
+            data,
+            affected_rows: self.affected_rows,
+            execution_time_ms: self.execution_time_ms,
+            truncated_cells: self.truncated_cells,
+        }
+    }
+}
+
+#[cfg(test)]
+impl QueryResult {
+    /// Build a result from column names and rows of values, for tests
+    ///
+    /// Every column is a nullable VARCHAR; adjust `columns` where a test
+    /// depends on the type.
+    pub(crate) fn from_values(columns: &[&str], rows: Vec<Vec<serde_json::Value>>) -> Self {
+        QueryResult {
+            columns: columns
+                .iter()
+                .map(|name| ColumnInfo {
+                    name: name.to_string(),
+                    data_type: "VARCHAR".to_string(),
+                    nullable: true,
+                    encoding: None,
+                    truncated: false,
+                })
+                .collect(),
+            rows: rows.into_iter().map(|values| Row { values }).collect(),
+            affected_rows: None,
+            execution_time_ms: 0,
+            truncated_cells: Vec::new(),
+        }
+    }
+}
+
 use crate::Result;
 use sqlx::{MySqlConnection, Row as SqlxRow, Column, TypeInfo, ValueRef};
 use sqlx::types::chrono;
 use std::time::Instant;
 use tracing::{info, debug, error};
 
+/// Name of the prepared statement used by syntax validation
+const VALIDATE_STATEMENT_NAME: &str = "mcp_validate_sql";
+
+/// Result of validating a statement's syntax on the server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SqlValidation {
+    /// Whether the server accepted the statement
+    pub valid: bool,
+    /// Server error message if the statement was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Number of `?` placeholders in the statement
+    pub param_count: usize,
+}
+
 /// Query processor for executing SQL queries against MySQL database
 pub struct QueryProcessor;
 
@@ -59,6 +380,15 @@ impl QueryProcessor {
     pub async fn execute_query(
         connection: &mut MySqlConnection,
         request: &QueryRequest,
+    ) -> Result<QueryResult> {
+        Self::execute_query_with_options(connection, request, &ValueConversionOptions::default()).await
+    }
+
+    /// Execute a SQL query, converting values with the given options
+    pub async fn execute_query_with_options(
+        connection: &mut MySqlConnection,
+        request: &QueryRequest,
+        options: &ValueConversionOptions,
     ) -> Result<QueryResult> {
         let start_time = Instant::now();
         
@@ -74,10 +404,10 @@ impl QueryProcessor {
         }
 
         // Determine query type based on the SQL statement
-        let sql_trimmed = request.sql.trim().to_uppercase();
+        let sql_trimmed = crate::sql::strip_leading_comments(&request.sql).to_uppercase();
         
-        let result = if sql_trimmed.starts_with("SELECT") {
-            Self::execute_select_query(connection, request).await
+        let result = if Self::returns_result_set(&sql_trimmed) {
+            Self::execute_select_query(connection, request, options).await
         } else if sql_trimmed.starts_with("INSERT") 
             || sql_trimmed.starts_with("UPDATE") 
             || sql_trimmed.starts_with("DELETE") {
@@ -100,10 +430,65 @@ impl QueryProcessor {
         }
     }
 
+    /// Check a statement's syntax on the server without executing it
+    ///
+    /// The statement is parsed with `PREPARE` and always deallocated again.
+    pub async fn validate_syntax(connection: &mut MySqlConnection, sql: &str) -> Result<SqlValidation> {
+        use sqlx::Executor;
+
+        let prepare = format!("PREPARE {} FROM {}", VALIDATE_STATEMENT_NAME, crate::sql::quote_string_literal(sql));
+        let prepared = connection.execute(prepare.as_str()).await;
+
+        let deallocate = format!("DEALLOCATE PREPARE {}", VALIDATE_STATEMENT_NAME);
+        if let Err(e) = connection.execute(deallocate.as_str()).await {
+            // Expected when PREPARE failed, as nothing was prepared
+            debug!("DEALLOCATE after validation failed: {}", e);
+        }
+
+        let param_count = crate::sql::count_placeholders(sql);
+        match prepared {
+            Ok(_) => Ok(SqlValidation { valid: true, error: None, param_count }),
+            Err(sqlx::Error::Database(e)) => Ok(SqlValidation {
+                valid: false,
+                error: Some(e.message().to_string()),
+                param_count,
+            }),
+            Err(e) => Err(ServerError::query_error(sql.to_string(), e)),
+        }
+    }
+
+    /// Describe the columns a query returns by preparing it, without fetching rows
+    pub async fn describe_columns(connection: &mut MySqlConnection, sql: &str) -> Result<Vec<ColumnInfo>> {
+        use sqlx::Executor;
+
+        let describe = connection.describe(sql).await
+            .map_err(|e| ServerError::query_error(sql.to_string(), e))?;
+
+        Ok(describe.columns()
+            .iter()
+            .enumerate()
+            .map(|(index, column)| ColumnInfo {
+                nullable: describe.nullable(index).unwrap_or(true),
+                ..ColumnInfo::from_mysql_column(column)
+            })
+            .collect())
+    }
+
+    /// Whether an (uppercased) statement produces a result set
+    fn returns_result_set(sql_upper: &str) -> bool {
+        ["SELECT", "SHOW", "DESCRIBE", "DESC", "EXPLAIN", "WITH"]
+            .iter()
+            .any(|keyword| {
+                sql_upper.starts_with(keyword)
+                    && !sql_upper[keyword.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+            })
+    }
+
     /// Execute a SELECT query and return the result set
     async fn execute_select_query(
         connection: &mut MySqlConnection,
         request: &QueryRequest,
+        options: &ValueConversionOptions,
     ) -> Result<QueryResult> {
         use sqlx::Executor;
 
@@ -122,24 +507,22 @@ impl QueryProcessor {
                 rows: vec![],
                 affected_rows: None,
                 execution_time_ms: 0, // Will be set by caller
+                truncated_cells: Vec::new(),
             });
         }
 
         // Extract column information from the first row
         let first_row = &rows[0];
-        let columns = first_row.columns()
+        let mut columns: Vec<ColumnInfo> = first_row.columns()
             .iter()
-            .map(|col| ColumnInfo {
-                name: col.name().to_string(),
-                data_type: col.type_info().name().to_string(),
-                nullable: true, // MySQL columns are nullable by default unless specified otherwise
-            })
+            .map(ColumnInfo::from_mysql_column)
             .collect();
 
         // Convert all rows to our Row format
+        let mut truncated_cells = Vec::new();
         let mut result_rows = Vec::new();
         for (row_index, row) in rows.iter().enumerate() {
-            match Self::convert_row_to_json_values(row) {
+            match Self::convert_row_to_json_values(row, row_index, options, &mut truncated_cells) {
                 Ok(values) => result_rows.push(Row { values }),
                 Err(e) => {
                     error!("Failed to convert row {} to JSON: {}", row_index, e);
@@ -151,11 +534,18 @@ impl QueryProcessor {
             }
         }
 
+        for &[_, column] in &truncated_cells {
+            if let Some(column) = columns.get_mut(column) {
+                column.truncated = true;
+            }
+        }
+
         Ok(QueryResult {
             columns,
             rows: result_rows,
             affected_rows: None,
             execution_time_ms: 0, // Will be set by caller
+            truncated_cells,
         })
     }
 
@@ -183,36 +573,270 @@ impl QueryProcessor {
             rows: vec![],
             affected_rows: Some(affected_rows),
             execution_time_ms: 0, // Will be set by caller
+            truncated_cells: Vec::new(),
         })
     }
 
     /// Convert a MySQL row to JSON values
-    fn convert_row_to_json_values(row: &sqlx::mysql::MySqlRow) -> Result<Vec<serde_json::Value>> {
+    ///
+    /// The `[row_index, column]` position of every value that had to be
+    /// truncated is appended to `truncated_cells`.
+    pub(crate) fn convert_row_to_json_values(
+        row: &sqlx::mysql::MySqlRow,
+        row_index: usize,
+        options: &ValueConversionOptions,
+        truncated_cells: &mut Vec<[usize; 2]>,
+    ) -> Result<Vec<serde_json::Value>> {
         let mut values = Vec::new();
         
         for (i, column) in row.columns().iter().enumerate() {
-            let value = Self::convert_mysql_value_to_json(row, i, column)?;
+            let (value, was_truncated) = Self::convert_mysql_value_to_json(row, i, column, options)?;
+            if was_truncated {
+                truncated_cells.push([row_index, i]);
+            }
             values.push(value);
         }
         
         Ok(values)
     }
 
+    /// Check whether a MySQL type name denotes a binary column
+    pub(crate) fn is_binary_type(type_name: &str) -> bool {
+        matches!(type_name, "BINARY" | "VARBINARY" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB")
+    }
+
+    /// Check whether a MySQL type name denotes a text column, the only kind `max_cell_length` cuts
+    ///
+    /// DECIMAL, temporal and large unsigned values are also returned as
+    /// strings, but cutting them would corrupt the value.
+    pub(crate) fn is_text_type(type_name: &str) -> bool {
+        matches!(
+            type_name,
+            "CHAR" | "VARCHAR" | "TINYTEXT" | "TEXT" | "MEDIUMTEXT" | "LONGTEXT" | "ENUM" | "SET"
+        )
+    }
+
+    /// Encode binary data as base64, truncating to `max_bytes` if set
+    ///
+    /// Returns the encoded string and whether the data was truncated.
+    pub fn encode_binary(bytes: &[u8], max_bytes: Option<usize>) -> (String, bool) {
+        use base64::{Engine as _, engine::general_purpose};
+
+        match max_bytes {
+            Some(max) if bytes.len() > max => (general_purpose::STANDARD.encode(&bytes[..max]), true),
+            _ => (general_purpose::STANDARD.encode(bytes), false),
+        }
+    }
+
     /// Convert a MySQL value to a JSON value
+    ///
+    /// Returns the converted value and whether it was truncated.
     fn convert_mysql_value_to_json(
         row: &sqlx::mysql::MySqlRow,
         column_index: usize,
         column: &sqlx::mysql::MySqlColumn,
+        options: &ValueConversionOptions,
+    ) -> Result<(serde_json::Value, bool)> {
+        let type_name = column.type_info().name();
+
+        // Binary types - convert to base64 string
+        if !row.try_get_raw(column_index)?.is_null() && Self::is_binary_type(type_name) {
+            let val: Vec<u8> = row.try_get(column_index)?;
+            let (encoded, truncated) = Self::encode_binary(&val, options.max_blob_bytes);
+            return Ok((serde_json::Value::String(encoded), truncated));
+        }
+
+        // Zero dates have no chrono equivalent and would fail to decode
+        if matches!(type_name, "DATE" | "DATETIME" | "TIMESTAMP") && !row.try_get_raw(column_index)?.is_null() {
+            let raw: &[u8] = row.try_get_unchecked(column_index)?;
+            if let Some(value) = Self::convert_zero_date(raw, type_name, options.zero_date_as_null) {
+                return Ok((value, false));
+            }
+        }
+
+        // NaN and infinities have no JSON number form
+        if matches!(type_name, "FLOAT" | "DOUBLE") && !row.try_get_raw(column_index)?.is_null() {
+            let val = match type_name {
+                "FLOAT" => row.try_get::<f32, _>(column_index)? as f64,
+                _ => row.try_get::<f64, _>(column_index)?,
+            };
+            return Ok((Self::convert_float(val, options.non_finite_float), false));
+        }
+
+        let mut value = Self::convert_scalar_value_to_json(row, column_index, type_name)?;
+        let depth_truncated = type_name == "JSON" && Self::truncate_json_depth(&mut value, options.max_json_depth);
+        let length_truncated = Self::is_text_type(type_name)
+            && options.max_cell_length.is_some_and(|max| Self::truncate_cell(&mut value, max));
+        Ok((value, depth_truncated || length_truncated))
+    }
+
+    /// Cut a string value to `max_chars` characters, noting how many bytes were dropped
+    ///
+    /// The kept text is followed by `…(truncated N bytes)`. Other values are left
+    /// alone. Returns whether the value was cut.
+    pub fn truncate_cell(value: &mut serde_json::Value, max_chars: usize) -> bool {
+        let serde_json::Value::String(text) = value else {
+            return false;
+        };
+        let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+            return false;
+        };
+
+        let dropped = text.len() - cut;
+        text.truncate(cut);
+        text.push_str(&format!("…(truncated {} bytes)", dropped));
+        true
+    }
+
+    /// Replace arrays and objects nested deeper than `max_depth` with a marker string
+    ///
+    /// The top-level value is at depth 1. Returns whether anything was cut.
+    pub fn truncate_json_depth(value: &mut serde_json::Value, max_depth: usize) -> bool {
+        match value {
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) if max_depth == 0 => {
+                *value = serde_json::Value::String(JSON_DEPTH_MARKER.to_string());
+                true
+            }
+            serde_json::Value::Array(items) => items
+                .iter_mut()
+                .map(|item| Self::truncate_json_depth(item, max_depth - 1))
+                .filter(|&cut| cut)
+                .count() > 0,
+            serde_json::Value::Object(map) => map
+                .values_mut()
+                .map(|item| Self::truncate_json_depth(item, max_depth - 1))
+                .filter(|&cut| cut)
+                .count() > 0,
+            _ => false,
+        }
+    }
+
+    /// Convert a floating point value, mapping NaN and infinities per `non_finite`
+    pub fn convert_float(val: f64, non_finite: NonFiniteFloat) -> serde_json::Value {
+        if let Some(num) = serde_json::Number::from_f64(val) {
+            return serde_json::Value::Number(num);
+        }
+
+        match non_finite {
+            NonFiniteFloat::Null => serde_json::Value::Null,
+            NonFiniteFloat::String if val.is_nan() => serde_json::Value::String("NaN".to_string()),
+            NonFiniteFloat::String if val > 0.0 => serde_json::Value::String("Infinity".to_string()),
+            NonFiniteFloat::String => serde_json::Value::String("-Infinity".to_string()),
+        }
+    }
+
+    /// Convert a raw DATE/DATETIME/TIMESTAMP value if it is MySQL's zero date
+    ///
+    /// Zero dates arrive as `0000-00-00[ 00:00:00[.000000]]` in the text
+    /// protocol and as an empty value in the binary protocol. They become NULL,
+    /// or their literal text when `as_null` is false. Returns `None` for any
+    /// other value.
+    pub fn convert_zero_date(raw: &[u8], type_name: &str, as_null: bool) -> Option<serde_json::Value> {
+        let text = match raw {
+            // Binary protocol: length 0
+            [0] if type_name == "DATE" => "0000-00-00",
+            [0] => "0000-00-00 00:00:00",
+            _ => std::str::from_utf8(raw)
+                .ok()
+                .filter(|text| text.starts_with("0000-00-00") && !text.bytes().any(|b| b.is_ascii_digit() && b != b'0'))?,
+        };
+
+        Some(if as_null { serde_json::Value::Null } else { serde_json::Value::String(text.to_string()) })
+    }
+
+    /// Convert a raw TIME value, failing like any other undecodable column
+    fn time_value(raw: &[u8], column_index: usize) -> Result<serde_json::Value> {
+        match Self::decode_time_duration(raw) {
+            Some(duration) => Ok(serde_json::Value::String(duration)),
+            None => Err(sqlx::Error::ColumnDecode {
+                index: column_index.to_string(),
+                source: format!("invalid TIME value: {:?}", raw).into(),
+            }.into()),
+        }
+    }
+
+    /// Decode a raw TIME value in either the text (`-838:59:59.5`) or binary protocol format
+    fn decode_time_duration(raw: &[u8]) -> Option<String> {
+        if let Ok(text) = std::str::from_utf8(raw) {
+            if let Some(formatted) = Self::parse_time_text(text) {
+                return Some(formatted);
+            }
+        }
+
+        // Binary: length, sign, days (u32 LE), hours, minutes, seconds, [micros (u32 LE)]
+        let (&len, rest) = raw.split_first()?;
+        if len == 0 {
+            return Some(Self::format_time_duration(false, 0, 0, 0, 0));
+        }
+        if rest.len() < 8 {
+            return None;
+        }
+        let days = u32::from_le_bytes(rest[1..5].try_into().ok()?);
+        let micros = match rest.get(8..12) {
+            Some(bytes) => u32::from_le_bytes(bytes.try_into().ok()?),
+            None => 0,
+        };
+        Some(Self::format_time_duration(
+            rest[0] != 0,
+            days * 24 + u32::from(rest[5]),
+            u32::from(rest[6]),
+            u32::from(rest[7]),
+            micros,
+        ))
+    }
+
+    /// Parse a TIME value in text form (`[-]H+:MM:SS[.f+]`)
+    fn parse_time_text(text: &str) -> Option<String> {
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (clock, fraction) = match unsigned.split_once('.') {
+            Some((clock, fraction)) => (clock, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut parts = clock.split(':');
+        let hours = parts.next()?.parse().ok()?;
+        let minutes = parts.next()?.parse().ok()?;
+        let seconds = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let micros = match fraction {
+            Some(fraction) if !fraction.is_empty() && fraction.len() <= 6 && fraction.bytes().all(|b| b.is_ascii_digit()) => {
+                format!("{:0<6}", fraction).parse().ok()?
+            }
+            Some(_) => return None,
+            None => 0,
+        };
+
+        Some(Self::format_time_duration(negative, hours, minutes, seconds, micros))
+    }
+
+    /// Format a TIME duration as `[-]HH:MM:SS[.ffffff]`, allowing more than 24 hours
+    pub fn format_time_duration(negative: bool, hours: u32, minutes: u32, seconds: u32, micros: u32) -> String {
+        let sign = if negative && (hours, minutes, seconds, micros) != (0, 0, 0, 0) { "-" } else { "" };
+        if micros > 0 {
+            format!("{}{:02}:{:02}:{:02}.{:06}", sign, hours, minutes, seconds, micros)
+        } else {
+            format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds)
+        }
+    }
+
+    /// Convert a non-binary MySQL value to a JSON value
+    fn convert_scalar_value_to_json(
+        row: &sqlx::mysql::MySqlRow,
+        column_index: usize,
+        type_name: &str,
     ) -> Result<serde_json::Value> {
 
-        
         // Check if the value is NULL first
         if row.try_get_raw(column_index)?.is_null() {
             return Ok(serde_json::Value::Null);
         }
 
-        let type_name = column.type_info().name();
-        
         match type_name {
             // Integer types
             "TINYINT" => {
@@ -255,23 +879,7 @@ impl QueryProcessor {
                 }
             }
             
-            // Floating point types
-            "FLOAT" => {
-                let val: f32 = row.try_get(column_index)?;
-                if let Some(num) = serde_json::Number::from_f64(val as f64) {
-                    Ok(serde_json::Value::Number(num))
-                } else {
-                    Ok(serde_json::Value::String(val.to_string()))
-                }
-            }
-            "DOUBLE" => {
-                let val: f64 = row.try_get(column_index)?;
-                if let Some(num) = serde_json::Number::from_f64(val) {
-                    Ok(serde_json::Value::Number(num))
-                } else {
-                    Ok(serde_json::Value::String(val.to_string()))
-                }
-            }
+            // FLOAT and DOUBLE are converted by `convert_float` before reaching here
             
             // Decimal types - convert to string to preserve precision
             "DECIMAL" | "NUMERIC" => {
@@ -302,22 +910,16 @@ impl QueryProcessor {
                 Ok(serde_json::Value::String(val))
             }
             
-            // Binary types - convert to base64 string
-            "BINARY" | "VARBINARY" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
-                let val: Vec<u8> = row.try_get(column_index)?;
-                use base64::{Engine as _, engine::general_purpose};
-                let base64_str = general_purpose::STANDARD.encode(&val);
-                Ok(serde_json::Value::String(base64_str))
-            }
-            
             // Date and time types
             "DATE" => {
                 let val: chrono::NaiveDate = row.try_get(column_index)?;
                 Ok(serde_json::Value::String(val.to_string()))
             }
             "TIME" => {
-                let val: chrono::NaiveTime = row.try_get(column_index)?;
-                Ok(serde_json::Value::String(val.to_string()))
+                // TIME is a signed duration that can exceed 24 hours, so it
+                // is decoded from the raw value rather than as a clock time
+                let raw: &[u8] = row.try_get_unchecked(column_index)?;
+                Self::time_value(raw, column_index)
             }
             "DATETIME" | "TIMESTAMP" => {
                 let val: chrono::NaiveDateTime = row.try_get(column_index)?;
@@ -356,7 +958,7 @@ impl QueryProcessor {
 mod tests {
     use super::*;
     use proptest::prelude::*;
-    use serde_json;
+    use serde_json::{self, json};
 
     // Property-based test generators
     prop_compose! {
@@ -369,6 +971,8 @@ mod tests {
                 name,
                 data_type,
                 nullable,
+                encoding: None,
+                truncated: false,
             }
         }
     }
@@ -397,13 +1001,15 @@ mod tests {
             columns in prop::collection::vec(arb_column_info(), 0..5),
             rows in prop::collection::vec(arb_row(), 0..100),
             affected_rows in prop::option::of(any::<u64>()),
-            execution_time_ms in any::<u64>()
+            execution_time_ms in any::<u64>(),
+            truncated_cells in prop::collection::vec(any::<[usize; 2]>(), 0..3)
         ) -> QueryResult {
             QueryResult {
                 columns,
                 rows,
                 affected_rows,
                 execution_time_ms,
+                truncated_cells,
             }
         }
     }
@@ -471,6 +1077,7 @@ mod tests {
         /// **Feature: mysql-mcp-server, Property 5: SELECT query execution**
         /// **Validates: Requirements 2.1**
         #[test]
+        #[allow(clippy::bool_comparison)]
         fn test_select_query_request_structure(request in arb_query_request()) {
             // Verify that the generated query request has the correct structure for SELECT queries
             prop_assert!(request.sql.trim().to_uppercase().starts_with("SELECT"));
@@ -513,4 +1120,266 @@ mod tests {
             prop_assert_eq!(query_result, deserialized);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_query_result_into_columnar() {
+        let mut result = QueryResult::from_values(&["id", "name"], vec![
+            vec![serde_json::json!(1), serde_json::json!("alice")],
+            vec![serde_json::json!(2), serde_json::Value::Null],
+            vec![serde_json::json!(3), serde_json::json!("carol")],
+        ]);
+        result.execution_time_ms = 5;
+
+        let columnar = result.into_columnar();
+
+        assert_eq!(columnar.columns.len(), 2);
+        assert_eq!(columnar.data["id"], serde_json::json!([1, 2, 3]));
+        assert_eq!(columnar.data["name"], serde_json::json!(["alice", null, "carol"]));
+        assert_eq!(columnar.execution_time_ms, 5);
+    }
+
+    #[test]
+    fn test_dedup_rows_removes_duplicates() {
+        let mut result = QueryResult::from_values(
+            &[],
+            [json!([1, "a"]), json!([2, "b"]), json!([1, "a"]), json!([1, null]), json!([2, "b"])]
+                .into_iter()
+                .map(|values| values.as_array().unwrap().clone())
+                .collect(),
+        );
+
+        assert_eq!(result.dedup_rows(), 2);
+        let remaining: Vec<_> = result.rows.iter().map(|row| serde_json::Value::Array(row.values.clone())).collect();
+        assert_eq!(remaining, vec![json!([1, "a"]), json!([2, "b"]), json!([1, null])]);
+    }
+
+    #[test]
+    fn test_schema_hash_tracks_column_shape() {
+        let mut result = reorder_fixture();
+        let hash = result.schema_hash();
+
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, reorder_fixture().schema_hash());
+
+        // Rows do not affect the hash
+        result.rows.clear();
+        assert_eq!(result.schema_hash(), hash);
+
+        result.columns[0].data_type = "BIGINT".to_string();
+        assert_ne!(result.schema_hash(), hash);
+
+        let mut nullable_changed = reorder_fixture();
+        nullable_changed.columns[1].nullable = false;
+        assert_ne!(nullable_changed.schema_hash(), hash);
+
+        let mut reordered = reorder_fixture();
+        reordered.reorder_columns(&["email".to_string()]).unwrap();
+        assert_ne!(reordered.schema_hash(), hash);
+    }
+
+    fn reorder_fixture() -> QueryResult {
+        QueryResult::from_values(&["id", "name", "email"], vec![vec![json!(1), json!("Ada"), json!("ada@example.com")]])
+    }
+
+    #[test]
+    fn test_reorder_columns() {
+        let names = |result: &QueryResult| result.columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+
+        let mut result = reorder_fixture();
+        result.reorder_columns(&["email".to_string(), "id".to_string(), "name".to_string()]).unwrap();
+        assert_eq!(names(&result), vec!["email", "id", "name"]);
+        assert_eq!(result.rows[0].values, vec![json!("ada@example.com"), json!(1), json!("Ada")]);
+
+        // Unlisted columns follow in their original order
+        let mut result = reorder_fixture();
+        result.reorder_columns(&["email".to_string()]).unwrap();
+        assert_eq!(names(&result), vec!["email", "id", "name"]);
+        assert_eq!(result.rows[0].values, vec![json!("ada@example.com"), json!(1), json!("Ada")]);
+    }
+
+    #[test]
+    fn test_sort_by_column() {
+        let mut result = QueryResult::from_values(&["price", "created", "misc"], vec![
+            vec![json!("10.50"), json!("2024-03-01"), json!("b")],
+            vec![json!("9.99"), json!(null), json!(2)],
+            vec![json!(null), json!("2023-12-31"), json!(null)],
+            vec![json!("100.00"), json!("2024-01-15"), json!(10)],
+        ]);
+        for (column, data_type) in result.columns.iter_mut().zip(["DECIMAL", "DATE", "JSON"]) {
+            column.data_type = data_type.to_string();
+        }
+        let values = |result: &QueryResult, index: usize| result.rows.iter().map(|row| row.values[index].clone()).collect::<Vec<_>>();
+
+        result.sort_by_column("price", false).unwrap();
+        assert_eq!(values(&result, 0), vec![json!(null), json!("9.99"), json!("10.50"), json!("100.00")]);
+
+        result.sort_by_column("created", true).unwrap();
+        assert_eq!(values(&result, 1), vec![json!("2024-03-01"), json!("2024-01-15"), json!("2023-12-31"), json!(null)]);
+
+        result.sort_by_column("misc", false).unwrap();
+        assert_eq!(values(&result, 2), vec![json!(null), json!(2), json!(10), json!("b")]);
+
+        assert!(matches!(result.sort_by_column("missing", false), Err(ServerError::Validation { .. })));
+    }
+
+    #[test]
+    fn test_truncated_cells_follow_rows_and_columns() {
+        let mut result = reorder_fixture();
+        result.rows = vec![
+            Row { values: vec![json!(2), json!("Bob…(truncated 3 bytes)"), json!("bob@example.com")] },
+            Row { values: vec![json!(1), json!("Ada"), json!("ada@example.com…(truncated 4 bytes)")] },
+            Row { values: vec![json!(2), json!("Bob…(truncated 3 bytes)"), json!("bob@example.com")] },
+        ];
+        result.truncated_cells = vec![[0, 1], [1, 2], [2, 1]];
+
+        assert_eq!(result.dedup_rows(), 1);
+        assert_eq!(result.truncated_cells, [[0, 1], [1, 2]]);
+
+        result.sort_by_column("id", false).unwrap();
+        assert_eq!(result.rows[0].values[0], json!(1));
+        assert_eq!(result.truncated_cells, [[0, 2], [1, 1]]);
+
+        result.reorder_columns(&["email".to_string()]).unwrap();
+        assert_eq!(result.rows[0].values[0], json!("ada@example.com…(truncated 4 bytes)"));
+        assert_eq!(result.truncated_cells, [[0, 0], [1, 2]]);
+
+        assert_eq!(result.into_columnar().truncated_cells, [[0, 0], [1, 2]]);
+    }
+
+    #[test]
+    fn test_convert_non_finite_float() {
+        let convert = QueryProcessor::convert_float;
+
+        assert_eq!(convert(f64::NAN, NonFiniteFloat::String), json!("NaN"));
+        assert_eq!(convert(f64::INFINITY, NonFiniteFloat::String), json!("Infinity"));
+        assert_eq!(convert(f64::NEG_INFINITY, NonFiniteFloat::String), json!("-Infinity"));
+        assert_eq!(convert(f64::INFINITY, NonFiniteFloat::Null), serde_json::Value::Null);
+        assert_eq!(convert(f64::NAN, NonFiniteFloat::Null), serde_json::Value::Null);
+        assert_eq!(convert(1.5e308, NonFiniteFloat::Null), json!(1.5e308));
+
+        let row = Row { values: vec![convert(f64::NAN, NonFiniteFloat::String)] };
+        assert!(serde_json::from_str::<serde_json::Value>(&serde_json::to_string(&row).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_truncate_json_depth() {
+        let mut value = json!({"a": {"b": {"c": [1, 2]}}, "d": [1, {"e": 2}], "f": 3});
+
+        assert!(QueryProcessor::truncate_json_depth(&mut value, 2));
+        assert_eq!(value, json!({"a": {"b": JSON_DEPTH_MARKER}, "d": [1, JSON_DEPTH_MARKER], "f": 3}));
+
+        let mut shallow = json!({"a": [1, 2]});
+        assert!(!QueryProcessor::truncate_json_depth(&mut shallow, 2));
+        assert_eq!(shallow, json!({"a": [1, 2]}));
+
+        let mut scalar = json!("text");
+        assert!(!QueryProcessor::truncate_json_depth(&mut scalar, 0));
+    }
+
+    #[test]
+    fn test_only_text_types_are_length_truncated() {
+        for text in ["CHAR", "VARCHAR", "TEXT", "LONGTEXT", "ENUM", "SET"] {
+            assert!(QueryProcessor::is_text_type(text), "{}", text);
+        }
+        for other in ["DECIMAL", "DATE", "DATETIME", "TIME", "BIGINT UNSIGNED", "DOUBLE", "JSON", "BLOB"] {
+            assert!(!QueryProcessor::is_text_type(other), "{}", other);
+        }
+    }
+
+    #[test]
+    fn test_truncate_cell() {
+        let mut long = json!("héllo world");
+        assert!(QueryProcessor::truncate_cell(&mut long, 5));
+        assert_eq!(long, json!("héllo…(truncated 6 bytes)"));
+
+        let mut short = json!("héllo");
+        assert!(!QueryProcessor::truncate_cell(&mut short, 5));
+        assert_eq!(short, json!("héllo"));
+
+        let mut number = json!(1234567);
+        assert!(!QueryProcessor::truncate_cell(&mut number, 2));
+        assert_eq!(number, json!(1234567));
+    }
+
+    #[test]
+    fn test_convert_zero_date() {
+        let convert = QueryProcessor::convert_zero_date;
+
+        assert_eq!(convert(b"0000-00-00", "DATE", true), Some(serde_json::Value::Null));
+        assert_eq!(convert(b"0000-00-00 00:00:00.000000", "DATETIME", true), Some(serde_json::Value::Null));
+        assert_eq!(convert(&[0], "DATE", false), Some(json!("0000-00-00")));
+        assert_eq!(convert(&[0], "TIMESTAMP", false), Some(json!("0000-00-00 00:00:00")));
+        assert_eq!(convert(b"0000-00-00 00:00:00", "DATETIME", false), Some(json!("0000-00-00 00:00:00")));
+
+        // Real dates are left to the regular conversion
+        assert_eq!(convert(b"2024-01-31", "DATE", true), None);
+        assert_eq!(convert(b"0000-00-00 10:00:00", "DATETIME", true), None);
+        assert_eq!(convert(&[4, 0xE8, 0x07, 1, 31], "DATE", true), None);
+    }
+
+    #[test]
+    fn test_reorder_columns_rejects_unknown_column() {
+        let mut result = reorder_fixture();
+
+        match result.reorder_columns(&["phone".to_string()]) {
+            Err(ServerError::Validation { message, .. }) => assert!(message.contains("'phone'")),
+            other => panic!("expected validation error, got {:?}", other),
+        }
+        assert_eq!(result.columns[0].name, "id");
+    }
+
+    #[test]
+    fn test_encode_binary_base64_and_truncation() {
+        let data = b"hello world";
+
+        let (encoded, truncated) = QueryProcessor::encode_binary(data, None);
+        assert_eq!(encoded, "aGVsbG8gd29ybGQ=");
+        assert!(!truncated);
+
+        let (encoded, truncated) = QueryProcessor::encode_binary(data, Some(5));
+        assert_eq!(encoded, "aGVsbG8=");
+        assert!(truncated);
+
+        // A limit equal to the length does not truncate
+        let (_, truncated) = QueryProcessor::encode_binary(data, Some(data.len()));
+        assert!(!truncated);
+
+        assert!(QueryProcessor::is_binary_type("LONGBLOB"));
+        assert!(!QueryProcessor::is_binary_type("TEXT"));
+    }
+
+    #[test]
+    fn test_returns_result_set() {
+        for sql in ["SELECT 1", "SHOW TABLES", "DESCRIBE users", "DESC users", "EXPLAIN SELECT 1", "WITH t AS (SELECT 1) SELECT * FROM t"] {
+            assert!(QueryProcessor::returns_result_set(sql), "{} should return rows", sql);
+        }
+        for sql in ["INSERT INTO t VALUES (1)", "DELETE FROM t", "SHOWCASE", "DESCEND"] {
+            assert!(!QueryProcessor::returns_result_set(sql), "{} should not return rows", sql);
+        }
+    }
+
+    #[test]
+    fn test_time_durations_keep_sign_and_range() {
+        assert_eq!(QueryProcessor::decode_time_duration(b"838:59:59").as_deref(), Some("838:59:59"));
+        assert_eq!(QueryProcessor::decode_time_duration(b"-12:30:05").as_deref(), Some("-12:30:05"));
+        assert_eq!(QueryProcessor::decode_time_duration(b"00:00:01.5").as_deref(), Some("00:00:01.500000"));
+        assert_eq!(QueryProcessor::format_time_duration(true, 100, 0, 0, 250), "-100:00:00.000250");
+
+        // Binary protocol: -34 days 22:59:59.000001 = -838:59:59.000001
+        let mut binary = vec![12, 1];
+        binary.extend_from_slice(&34u32.to_le_bytes());
+        binary.extend_from_slice(&[22, 59, 59]);
+        binary.extend_from_slice(&1u32.to_le_bytes());
+        assert_eq!(QueryProcessor::decode_time_duration(&binary).as_deref(), Some("-838:59:59.000001"));
+        assert_eq!(QueryProcessor::decode_time_duration(&[0]).as_deref(), Some("00:00:00"));
+    }
+
+    #[test]
+    fn test_undecodable_time_is_an_error() {
+        assert_eq!(QueryProcessor::time_value(b"12:30:05", 0).unwrap(), json!("12:30:05"));
+
+        // A binary value cut short is reported instead of being read as NULL
+        let error = QueryProcessor::time_value(&[8, 0, 1], 3).unwrap_err();
+        assert!(error.detailed_message().contains("invalid TIME value"), "{}", error.detailed_message());
+    }
+}
@@ -0,0 +1,126 @@
+//! Replica status parsing for replication lag checks
+
+use serde::{Deserialize, Serialize};
+use crate::query::QueryResult;
+use serde_json::Value;
+
+/// Status of one replication channel, from `SHOW REPLICA STATUS`
+///
+/// Column names from both `SHOW REPLICA STATUS` (8.0.22+) and the older
+/// `SHOW SLAVE STATUS` are understood.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplicationChannel {
+    /// Replication channel name (empty for the default channel)
+    pub channel: String,
+    /// Host of the source server
+    pub source_host: Option<String>,
+    /// Seconds the replica is behind the source (null when not replicating)
+    pub seconds_behind_source: Option<u64>,
+    /// Whether the I/O thread is running
+    pub io_running: bool,
+    /// Whether the SQL thread is running
+    pub sql_running: bool,
+    /// Source binary log file and position executed so far
+    pub source_log_file: Option<String>,
+    pub exec_source_log_pos: Option<u64>,
+    /// GTID set executed on the replica
+    pub executed_gtid_set: Option<String>,
+    /// Last I/O thread error
+    pub last_io_errno: Option<u64>,
+    pub last_io_error: Option<String>,
+    /// Last SQL thread error
+    pub last_sql_errno: Option<u64>,
+    pub last_sql_error: Option<String>,
+}
+
+/// Parse the rows of `SHOW REPLICA STATUS`, one per channel
+///
+/// An empty result means the server is not a replica.
+pub fn parse_replica_status(result: &QueryResult) -> Vec<ReplicationChannel> {
+    result.rows
+        .iter()
+        .map(|row| {
+            let field = |names: &[&str]| {
+                result.columns
+                    .iter()
+                    .position(|c| names.iter().any(|name| c.name.eq_ignore_ascii_case(name)))
+                    .and_then(|index| row.values.get(index))
+                    .filter(|value| !value.is_null())
+            };
+            let text = |names: &[&str]| {
+                field(names).map(|value| match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+            };
+            let number = |names: &[&str]| {
+                field(names).and_then(|value| value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok())))
+            };
+            let running = |names: &[&str]| text(names).is_some_and(|state| state.eq_ignore_ascii_case("Yes"));
+
+            ReplicationChannel {
+                channel: text(&["Channel_Name"]).unwrap_or_default(),
+                source_host: text(&["Source_Host", "Master_Host"]),
+                seconds_behind_source: number(&["Seconds_Behind_Source", "Seconds_Behind_Master"]),
+                io_running: running(&["Replica_IO_Running", "Slave_IO_Running"]),
+                sql_running: running(&["Replica_SQL_Running", "Slave_SQL_Running"]),
+                source_log_file: text(&["Relay_Source_Log_File", "Relay_Master_Log_File"]),
+                exec_source_log_pos: number(&["Exec_Source_Log_Pos", "Exec_Master_Log_Pos"]),
+                executed_gtid_set: text(&["Executed_Gtid_Set"]).filter(|gtid| !gtid.is_empty()),
+                last_io_errno: number(&["Last_IO_Errno"]),
+                last_io_error: text(&["Last_IO_Error"]).filter(|e| !e.is_empty()),
+                last_sql_errno: number(&["Last_SQL_Errno"]),
+                last_sql_error: text(&["Last_SQL_Error"]).filter(|e| !e.is_empty()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_replica_status() {
+        let result = QueryResult::from_values(
+            &["Source_Host", "Replica_IO_Running", "Replica_SQL_Running", "Seconds_Behind_Source",
+              "Relay_Source_Log_File", "Exec_Source_Log_Pos", "Last_IO_Errno", "Last_IO_Error",
+              "Last_SQL_Errno", "Last_SQL_Error", "Executed_Gtid_Set", "Channel_Name"],
+            vec![vec![
+                json!("primary.internal"), json!("Yes"), json!("No"), json!(null),
+                json!("binlog.000042"), json!(1337), json!(0), json!(""),
+                json!(1146), json!("Table 'app.gone' doesn't exist"), json!("uuid:1-100"), json!(""),
+            ]],
+        );
+
+        let channels = parse_replica_status(&result);
+
+        assert_eq!(channels.len(), 1);
+        let channel = &channels[0];
+        assert_eq!(channel.source_host.as_deref(), Some("primary.internal"));
+        assert!(channel.io_running);
+        assert!(!channel.sql_running);
+        assert_eq!(channel.seconds_behind_source, None);
+        assert_eq!(channel.exec_source_log_pos, Some(1337));
+        assert_eq!(channel.last_io_error, None);
+        assert_eq!(channel.last_sql_errno, Some(1146));
+        assert_eq!(channel.executed_gtid_set.as_deref(), Some("uuid:1-100"));
+    }
+
+    #[test]
+    fn test_parse_legacy_slave_status() {
+        let result = QueryResult::from_values(
+            &["Master_Host", "Slave_IO_Running", "Slave_SQL_Running", "Seconds_Behind_Master"],
+            vec![vec![json!("db1"), json!("Yes"), json!("Yes"), json!("12")]],
+        );
+
+        let channels = parse_replica_status(&result);
+
+        assert_eq!(channels[0].source_host.as_deref(), Some("db1"));
+        assert!(channels[0].io_running && channels[0].sql_running);
+        assert_eq!(channels[0].seconds_behind_source, Some(12));
+
+        assert!(parse_replica_status(&QueryResult::from_values(&["Source_Host"], vec![])).is_empty());
+    }
+}
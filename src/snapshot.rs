@@ -0,0 +1,178 @@
+//! Query result snapshots for detecting result changes
+
+use serde::{Deserialize, Serialize};
+use crate::query::{QueryResult, Row};
+use std::collections::HashMap;
+
+/// Maximum number of differing rows reported in each direction
+const MAX_DIFF_ROWS: usize = 20;
+
+/// A canonicalized query result stored under a name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuerySnapshot {
+    /// SQL query the snapshot was taken from
+    pub sql: String,
+    /// Column names and data types, in result order
+    pub columns: Vec<(String, String)>,
+    /// Result rows, sorted so that row order does not matter
+    pub rows: Vec<Row>,
+    /// Checksum over the canonical columns and rows
+    pub checksum: String,
+    /// When the snapshot was taken (RFC 3339)
+    pub created_at: String,
+}
+
+/// Differences between a stored snapshot and a fresh result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotDiff {
+    /// Whether the fresh result matches the snapshot
+    pub matches: bool,
+    /// Checksum of the stored snapshot
+    pub expected_checksum: String,
+    /// Checksum of the fresh result
+    pub actual_checksum: String,
+    /// Whether the column names or types changed
+    pub columns_changed: bool,
+    /// Number of rows in the snapshot and in the fresh result
+    pub expected_row_count: usize,
+    pub actual_row_count: usize,
+    /// Rows present in the snapshot but missing from the fresh result (bounded)
+    pub missing_rows: Vec<Row>,
+    /// Rows present in the fresh result but not in the snapshot (bounded)
+    pub unexpected_rows: Vec<Row>,
+}
+
+impl QuerySnapshot {
+    /// Build a snapshot from a query result
+    pub fn from_result(sql: &str, result: &QueryResult) -> Self {
+        let columns: Vec<(String, String)> = result.columns
+            .iter()
+            .map(|c| (c.name.clone(), c.data_type.clone()))
+            .collect();
+
+        let mut rows = result.rows.clone();
+        rows.sort_by_cached_key(|row| serde_json::to_string(&row.values).unwrap_or_default());
+
+        let canonical = serde_json::to_string(&(&columns, &rows)).unwrap_or_default();
+
+        Self {
+            sql: sql.to_string(),
+            columns,
+            rows,
+            checksum: format!("{:016x}", fnv1a_64(canonical.as_bytes())),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Compare this snapshot against a fresh snapshot of the same query
+    pub fn diff(&self, actual: &QuerySnapshot) -> SnapshotDiff {
+        let columns_changed = self.columns != actual.columns;
+
+        let mut expected_counts: HashMap<String, usize> = HashMap::new();
+        for row in &self.rows {
+            *expected_counts.entry(row_key(row)).or_insert(0) += 1;
+        }
+
+        let mut unexpected_rows = Vec::new();
+        for row in &actual.rows {
+            match expected_counts.get_mut(&row_key(row)) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => {
+                    if unexpected_rows.len() < MAX_DIFF_ROWS {
+                        unexpected_rows.push(row.clone());
+                    }
+                }
+            }
+        }
+
+        let mut missing_rows = Vec::new();
+        for row in &self.rows {
+            if missing_rows.len() >= MAX_DIFF_ROWS {
+                break;
+            }
+            if let Some(count) = expected_counts.get_mut(&row_key(row)) {
+                if *count > 0 {
+                    *count -= 1;
+                    missing_rows.push(row.clone());
+                }
+            }
+        }
+
+        SnapshotDiff {
+            matches: self.checksum == actual.checksum,
+            expected_checksum: self.checksum.clone(),
+            actual_checksum: actual.checksum.clone(),
+            columns_changed,
+            expected_row_count: self.rows.len(),
+            actual_row_count: actual.rows.len(),
+            missing_rows,
+            unexpected_rows,
+        }
+    }
+}
+
+/// Canonical string key for a row
+fn row_key(row: &Row) -> String {
+    serde_json::to_string(&row.values).unwrap_or_default()
+}
+
+/// 64-bit FNV-1a hash, stable across runs and platforms
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result_with_rows(rows: Vec<Vec<serde_json::Value>>) -> QueryResult {
+        QueryResult::from_values(&["id", "name"], rows)
+    }
+
+    #[test]
+    fn test_snapshot_matches_rerun_in_different_order() {
+        let sql = "SELECT id, name FROM users";
+        let baseline = QuerySnapshot::from_result(sql, &result_with_rows(vec![
+            vec![json!(1), json!("alice")],
+            vec![json!(2), json!("bob")],
+        ]));
+        let rerun = QuerySnapshot::from_result(sql, &result_with_rows(vec![
+            vec![json!(2), json!("bob")],
+            vec![json!(1), json!("alice")],
+        ]));
+
+        let diff = baseline.diff(&rerun);
+
+        assert!(diff.matches);
+        assert_eq!(diff.expected_checksum, diff.actual_checksum);
+        assert!(diff.missing_rows.is_empty());
+        assert!(diff.unexpected_rows.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_reports_mismatch() {
+        let sql = "SELECT id, name FROM users";
+        let baseline = QuerySnapshot::from_result(sql, &result_with_rows(vec![
+            vec![json!(1), json!("alice")],
+            vec![json!(2), json!("bob")],
+        ]));
+        let changed = QuerySnapshot::from_result(sql, &result_with_rows(vec![
+            vec![json!(1), json!("alice")],
+            vec![json!(2), json!("robert")],
+            vec![json!(3), json!("carol")],
+        ]));
+
+        let diff = baseline.diff(&changed);
+
+        assert!(!diff.matches);
+        assert!(!diff.columns_changed);
+        assert_eq!(diff.expected_row_count, 2);
+        assert_eq!(diff.actual_row_count, 3);
+        assert_eq!(diff.missing_rows, vec![Row { values: vec![json!(2), json!("bob")] }]);
+        assert_eq!(diff.unexpected_rows.len(), 2);
+    }
+}
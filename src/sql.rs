@@ -0,0 +1,549 @@
+//! Lightweight SQL text scanning
+//!
+//! This is not a parser: it only tells code apart from string literals,
+//! quoted identifiers and comments, which is enough to count placeholders
+//! or find statement separators without being fooled by their contents.
+
+use serde::Serialize;
+
+/// Call `f` with the byte offset of every character outside literals and comments
+fn for_each_code_char(sql: &str, mut f: impl FnMut(usize, char)) {
+    let mut chars = sql.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                // Quoted string or identifier; a doubled quote or (outside
+                // identifiers) a backslash escapes the next character
+                while let Some((_, q)) = chars.next() {
+                    if q == '\\' && c != '`' {
+                        chars.next();
+                    } else if q == c {
+                        if chars.peek().map(|(_, next)| *next) == Some(c) {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            '#' => skip_line(&mut chars),
+            // MySQL only treats `--` as a comment when followed by whitespace
+            '-' if sql[i..].starts_with("--") && sql[i + 2..].chars().next().is_none_or(char::is_whitespace) => {
+                skip_line(&mut chars)
+            }
+            '/' if chars.peek().map(|(_, next)| *next) == Some('*') => {
+                chars.next();
+                let mut previous = '\0';
+                for (_, q) in chars.by_ref() {
+                    if previous == '*' && q == '/' {
+                        break;
+                    }
+                    previous = q;
+                }
+            }
+            _ => f(i, c),
+        }
+    }
+}
+
+fn skip_line(chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>) {
+    for (_, c) in chars.by_ref() {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+/// Count `?` placeholders outside string literals and comments
+pub fn count_placeholders(sql: &str) -> usize {
+    let mut count = 0;
+    for_each_code_char(sql, |_, c| {
+        if c == '?' {
+            count += 1;
+        }
+    });
+    count
+}
+
+/// Remove one trailing semicolon, along with the whitespace and comments around it
+///
+/// Returns the SQL unchanged if it does not end with a semicolon.
+pub fn strip_trailing_semicolon(sql: &str) -> &str {
+    let mut last = None;
+    for_each_code_char(sql, |i, c| {
+        if !c.is_whitespace() {
+            last = Some((i, c));
+        }
+    });
+
+    match last {
+        Some((i, ';')) => sql[..i].trim_end(),
+        _ => sql,
+    }
+}
+
+/// Whether the SQL holds more than one statement
+///
+/// A single trailing semicolon does not count as a statement separator.
+pub fn has_multiple_statements(sql: &str) -> bool {
+    let mut separators = 0;
+    for_each_code_char(strip_trailing_semicolon(sql), |_, c| {
+        if c == ';' {
+            separators += 1;
+        }
+    });
+    separators > 0
+}
+
+/// Skip the whitespace and comments before the first token of a statement
+pub fn strip_leading_comments(sql: &str) -> &str {
+    let mut first = None;
+    for_each_code_char(sql, |i, c| {
+        if first.is_none() && !c.is_whitespace() {
+            first = Some(i);
+        }
+    });
+    &sql[first.unwrap_or(sql.len())..]
+}
+
+/// Whether `text` is a single plain `/* ... */` comment
+///
+/// Executable (`/*! ... */`) and optimizer hint (`/*+ ... */`) comments are
+/// not plain, since MySQL acts on their contents.
+pub fn is_plain_comment(text: &str) -> bool {
+    let text = text.trim();
+    text.len() >= 4
+        && text.starts_with("/*")
+        && !text.starts_with("/*!")
+        && !text.starts_with("/*+")
+        && text.ends_with("*/")
+        && !text[2..text.len() - 2].contains("*/")
+        && !text.contains(';')
+}
+
+/// Normalize a statement into a fingerprint that does not contain its data
+///
+/// String and numeric literals become `?`, comments are dropped and runs of
+/// whitespace are collapsed, so statements differing only in their values
+/// share a fingerprint.
+pub fn fingerprint(sql: &str) -> String {
+    let mut code = Vec::new();
+    for_each_code_char(sql, |i, c| code.push((i, c)));
+
+    let mut out = String::with_capacity(sql.len());
+    let mut end = 0;
+    let mut in_number = false;
+    // A final whitespace sentinel flushes a trailing literal or comment
+    for (i, c) in code.into_iter().chain(std::iter::once((sql.len(), ' '))) {
+        if i > end {
+            let skipped = &sql[end..i];
+            match skipped.chars().next() {
+                Some('\'' | '"') => out.push('?'),
+                Some('`') => out.push_str(skipped),
+                _ if !out.ends_with(' ') => out.push(' '),
+                _ => {}
+            }
+            in_number = false;
+        }
+        end = i + c.len_utf8();
+
+        if in_number && (c.is_ascii_digit() || c == '.') {
+            continue;
+        }
+        in_number = false;
+
+        if c.is_whitespace() {
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+        } else if c.is_ascii_digit() && !out.ends_with(|p: char| p.is_alphanumeric() || p == '_' || p == '$') {
+            out.push('?');
+            in_number = true;
+        } else {
+            out.push(c);
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+/// Uppercased words outside literals and comments, with their parenthesis depth
+fn code_words(sql: &str) -> Vec<(String, usize)> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut depth = 0usize;
+    let mut last_end = 0;
+
+    for_each_code_char(sql, |i, c| {
+        // Skipped literals and comments also end a word
+        if i != last_end && !word.is_empty() {
+            words.push((std::mem::take(&mut word), depth));
+        }
+        last_end = i + c.len_utf8();
+
+        if c.is_alphanumeric() || c == '_' || c == '$' {
+            word.extend(c.to_uppercase());
+            return;
+        }
+        if !word.is_empty() {
+            words.push((std::mem::take(&mut word), depth));
+        }
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    });
+    if !word.is_empty() {
+        words.push((word, depth));
+    }
+
+    words
+}
+
+/// Whether the SQL holds a `/*! ... */` comment, whose contents MySQL executes
+fn has_executable_comment(sql: &str) -> bool {
+    let mut skipped = Vec::new();
+    let mut last_end = 0;
+    for_each_code_char(sql, |i, c| {
+        if i != last_end {
+            skipped.push(last_end..i);
+        }
+        last_end = i + c.len_utf8();
+    });
+    skipped.push(last_end..sql.len());
+
+    skipped.into_iter().any(|range| sql[range].contains("/*!"))
+}
+
+/// How a statement was classified by the read-only policy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Classification {
+    /// Whether the statement is allowed by the read-only policy
+    pub is_read_only: bool,
+    /// Statement keyword that decided the classification, e.g. `SELECT` or `DELETE`
+    pub statement_type: String,
+    /// Why the statement was classified this way
+    pub reasons: Vec<String>,
+}
+
+/// Statement keywords that can follow `WITH` or `EXPLAIN`
+const STATEMENT_KEYWORDS: &[&str] = &["SELECT", "TABLE", "INSERT", "UPDATE", "DELETE", "REPLACE"];
+
+/// Classify a statement as read-only or not
+///
+/// SELECT (including `WITH ... SELECT`), SHOW, DESCRIBE and EXPLAIN are
+/// read-only, except `SELECT ... INTO OUTFILE/DUMPFILE`, which writes a
+/// file, and `EXPLAIN ANALYZE` of a write, which runs it. Leading comments
+/// are skipped; several statements and `/*! */` comments, which MySQL
+/// executes, are never read-only.
+pub fn classify(sql: &str) -> Classification {
+    let mut reasons = Vec::new();
+    let classification = |is_read_only: bool, statement_type: &str, mut reasons: Vec<String>, reason: String| {
+        reasons.push(reason);
+        Classification { is_read_only, statement_type: statement_type.to_string(), reasons }
+    };
+
+    if has_multiple_statements(sql) {
+        return classification(false, "MULTIPLE", reasons, "Contains more than one statement".to_string());
+    }
+    let words = code_words(strip_trailing_semicolon(sql));
+    let Some((first, _)) = words.first() else {
+        return classification(false, "EMPTY", reasons, "No statement found".to_string());
+    };
+    if has_executable_comment(sql) {
+        return classification(false, first, reasons, "Contains a /*! */ comment, whose contents MySQL executes".to_string());
+    }
+    if strip_leading_comments(sql).len() != sql.trim_start().len() {
+        reasons.push("Leading comments were skipped".to_string());
+    }
+
+    // The statement a WITH clause or EXPLAIN applies to
+    let target = |from: usize| {
+        words[from..]
+            .iter()
+            .find(|(word, depth)| *depth == 0 && STATEMENT_KEYWORDS.contains(&word.as_str()))
+            .map(|(word, _)| word.as_str())
+    };
+
+    let (statement_type, explained) = match first.as_str() {
+        "WITH" => match target(1) {
+            Some(statement) => {
+                reasons.push(format!("WITH clause introduces a {} statement", statement));
+                (statement, false)
+            }
+            None => return classification(false, "WITH", reasons, "WITH clause is not followed by a statement".to_string()),
+        },
+        "EXPLAIN" | "DESCRIBE" | "DESC" if words.get(1).is_some_and(|(word, _)| word == "ANALYZE") => {
+            // EXPLAIN ANALYZE runs the statement; CTE bodies are nested, so the
+            // first top-level keyword is the statement even after WITH
+            let Some(statement) = target(2) else {
+                return classification(false, "EXPLAIN", reasons, "EXPLAIN ANALYZE target statement not recognized".to_string());
+            };
+            if !matches!(statement, "SELECT" | "TABLE") {
+                return classification(false, statement, reasons, format!("EXPLAIN ANALYZE executes the {} statement", statement));
+            }
+            reasons.push("EXPLAIN ANALYZE executes the SELECT statement".to_string());
+            (statement, true)
+        }
+        "EXPLAIN" | "DESCRIBE" | "DESC" => {
+            return classification(true, first, reasons, format!("{} does not execute the statement", first));
+        }
+        "SHOW" => return classification(true, "SHOW", reasons, "SHOW statements only read metadata".to_string()),
+        other => (other, false),
+    };
+
+    if statement_type != "SELECT" && statement_type != "TABLE" {
+        return classification(false, statement_type, reasons, format!("{} statements are not read-only", statement_type));
+    }
+
+    if let Some(window) = words.windows(2).find(|pair| pair[0].0 == "INTO" && matches!(pair[1].0.as_str(), "OUTFILE" | "DUMPFILE")) {
+        return classification(false, statement_type, reasons, format!("SELECT ... INTO {} writes a file on the server", window[1].0));
+    }
+    if words.windows(2).any(|pair| pair[0].0 == "FOR" && matches!(pair[1].0.as_str(), "UPDATE" | "SHARE")) {
+        reasons.push("Takes row locks until the statement completes".to_string());
+    }
+
+    let reason = if explained {
+        "Only reads data".to_string()
+    } else {
+        format!("{} statements only read data", statement_type)
+    };
+    classification(true, statement_type, reasons, reason)
+}
+
+/// A token of SQL code: a word, a backtick-quoted identifier or a punctuation character
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Word,
+    QuotedIdentifier,
+    Punct(char),
+}
+
+/// Tokens outside literals and comments, with their byte ranges
+fn code_tokens(sql: &str) -> Vec<(Token, std::ops::Range<usize>)> {
+    let mut tokens: Vec<(Token, std::ops::Range<usize>)> = Vec::new();
+    let mut last_end = 0;
+
+    for_each_code_char(sql, |i, c| {
+        // A skipped region starting with a backtick is a quoted identifier
+        if i != last_end && sql[last_end..].starts_with('`') {
+            let mut end = last_end + 1;
+            while let Some(offset) = sql[end..i].find('`') {
+                end += offset + 1;
+                if !sql[end..i].starts_with('`') {
+                    break;
+                }
+                end += 1;
+            }
+            tokens.push((Token::QuotedIdentifier, last_end..end));
+        }
+        let contiguous = i == last_end;
+        last_end = i + c.len_utf8();
+
+        if c.is_alphanumeric() || c == '_' || c == '$' {
+            match tokens.last_mut() {
+                Some((Token::Word, range)) if contiguous && range.end == i => range.end = last_end,
+                _ => tokens.push((Token::Word, i..last_end)),
+            }
+        } else if !c.is_whitespace() {
+            tokens.push((Token::Punct(c), i..last_end));
+        }
+    });
+    if sql[last_end..].starts_with('`') {
+        tokens.push((Token::QuotedIdentifier, last_end..sql.trim_end().len()));
+    }
+
+    tokens
+}
+
+/// Keywords that end a FROM clause's table list
+const FROM_LIST_END: &[&str] = &["WHERE", "GROUP", "ORDER", "HAVING", "LIMIT", "UNION", "ON", "USING", "WINDOW", "FOR", "INTO", "LOCK"];
+
+/// Rename unqualified table references according to `rewrites`
+///
+/// Only names in table position are renamed: right after FROM or JOIN, or
+/// after a comma in a FROM list. String literals, comments, column names
+/// and database-qualified tables (`db.table`) are left alone. Names match
+/// case-sensitively, as table names do on most MySQL servers. Returns the
+/// SQL unchanged if nothing was renamed.
+pub fn rewrite_tables(sql: &str, rewrites: &std::collections::HashMap<String, String>) -> String {
+    if rewrites.is_empty() {
+        return sql.to_string();
+    }
+
+    let tokens = code_tokens(sql);
+    let mut replacements = Vec::new();
+    let mut depth = 0usize;
+    // Depths of the FROM lists being read, innermost last
+    let mut from_depths: Vec<usize> = Vec::new();
+    let mut expect_table = false;
+
+    for (index, (token, range)) in tokens.iter().enumerate() {
+        let text = &sql[range.clone()];
+        match token {
+            Token::Punct('(') => {
+                depth += 1;
+                expect_table = false;
+            }
+            Token::Punct(')') => {
+                depth = depth.saturating_sub(1);
+                from_depths.retain(|&from| from <= depth);
+            }
+            Token::Punct(',') => expect_table = from_depths.last() == Some(&depth),
+            Token::Punct(_) => expect_table = false,
+            Token::Word if matches!(text.to_uppercase().as_str(), "FROM" | "JOIN" | "STRAIGHT_JOIN") => {
+                if text.eq_ignore_ascii_case("FROM") && from_depths.last() != Some(&depth) {
+                    from_depths.push(depth);
+                }
+                expect_table = true;
+            }
+            Token::Word if FROM_LIST_END.contains(&text.to_uppercase().as_str()) => {
+                if from_depths.last() == Some(&depth) {
+                    from_depths.pop();
+                }
+                expect_table = false;
+            }
+            Token::Word | Token::QuotedIdentifier if expect_table => {
+                expect_table = false;
+                let qualified = matches!(tokens.get(index + 1), Some((Token::Punct('.'), _)));
+                let name = match token {
+                    Token::QuotedIdentifier => text.trim_matches('`').replace("``", "`"),
+                    _ => text.to_string(),
+                };
+                if let (false, Some(new_name)) = (qualified, rewrites.get(&name)) {
+                    replacements.push((range.clone(), format!("`{}`", new_name.replace('`', "``"))));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = sql.to_string();
+    for (range, replacement) in replacements.into_iter().rev() {
+        out.replace_range(range, &replacement);
+    }
+    out
+}
+
+/// Quote a value as a SQL string literal
+pub fn quote_string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_placeholders_ignores_literals_and_comments() {
+        assert_eq!(count_placeholders("SELECT * FROM t WHERE a = ? AND b IN (?, ?)"), 3);
+        assert_eq!(count_placeholders("SELECT '?', \"it\\\"s ?\", `col?` FROM t WHERE a = ?"), 1);
+        assert_eq!(count_placeholders("SELECT 'it''s ?' -- trailing ?\n FROM t # also ?\n WHERE a = ? /* ? */"), 1);
+        assert_eq!(count_placeholders("SELECT 1"), 0);
+    }
+
+    #[test]
+    fn test_trailing_semicolon_is_not_a_second_statement() {
+        assert_eq!(strip_trailing_semicolon("SELECT 1;"), "SELECT 1");
+        assert_eq!(strip_trailing_semicolon("SELECT 1 ;  \n"), "SELECT 1");
+        assert_eq!(strip_trailing_semicolon("SELECT 1; -- done"), "SELECT 1");
+        assert_eq!(strip_trailing_semicolon("SELECT ';'"), "SELECT ';'");
+        assert_eq!(strip_trailing_semicolon("SELECT 1"), "SELECT 1");
+
+        assert!(!has_multiple_statements("SELECT 1;"));
+        assert!(!has_multiple_statements("SELECT 'a;b' FROM t /* ; */"));
+        assert!(has_multiple_statements("SELECT 1; DROP TABLE users"));
+        assert!(has_multiple_statements("SELECT 1;;"));
+    }
+
+    #[test]
+    fn test_comment_helpers() {
+        assert_eq!(strip_leading_comments("/* mcp:env=prod */ SELECT 1"), "SELECT 1");
+        assert_eq!(strip_leading_comments("  -- note\n# more\nSELECT 1"), "SELECT 1");
+        assert_eq!(strip_leading_comments("/* only */"), "");
+
+        assert!(is_plain_comment("/* mcp:env=prod */"));
+        assert!(!is_plain_comment("/* a */ DROP TABLE t /* b */"));
+        assert!(!is_plain_comment("/* a */; DROP TABLE t; /* b */"));
+        assert!(!is_plain_comment("/*! DROP TABLE t */"));
+        assert!(!is_plain_comment("-- note"));
+    }
+
+    #[test]
+    fn test_classify_statements() {
+        let read_only = |sql: &str| classify(sql).is_read_only;
+
+        assert!(read_only("SELECT * FROM users"));
+        assert!(read_only("show tables"));
+        assert!(read_only("DESC users"));
+        assert!(read_only("-- lookup\nSELECT 1;"));
+        assert!(!read_only("SELECTED FROM t"));
+
+        let with = classify("WITH recent AS (SELECT * FROM orders WHERE id > 10) SELECT * FROM recent");
+        assert!(with.is_read_only);
+        assert_eq!(with.statement_type, "SELECT");
+
+        let delete = classify("WITH old AS (SELECT id FROM t) DELETE FROM t WHERE id IN (SELECT id FROM old)");
+        assert!(!delete.is_read_only);
+        assert_eq!(delete.statement_type, "DELETE");
+
+        let hidden = classify("/* SELECT */ DELETE FROM users -- SELECT");
+        assert!(!hidden.is_read_only);
+        assert_eq!(hidden.statement_type, "DELETE");
+        assert!(hidden.reasons.contains(&"Leading comments were skipped".to_string()));
+
+        assert!(!read_only("SELECT * FROM users INTO OUTFILE '/tmp/users.csv'"));
+        assert!(!read_only("EXPLAIN ANALYZE DELETE FROM users"));
+        assert!(read_only("EXPLAIN ANALYZE SELECT * FROM users"));
+        assert!(!read_only("EXPLAIN ANALYZE WITH t AS (SELECT 1) DELETE FROM users"));
+        assert!(read_only("EXPLAIN DELETE FROM users"));
+        assert!(!read_only("SELECT 1; DELETE FROM users"));
+        assert!(!read_only("/* nothing */"));
+        assert!(!read_only("SELECT 1 /*! ; DROP TABLE users */"));
+        assert!(read_only("SELECT /*+ MAX_EXECUTION_TIME(1000) */ * FROM users"));
+    }
+
+    #[test]
+    fn test_rewrite_tables_only_touches_table_references() {
+        let rewrites: std::collections::HashMap<String, String> =
+            [("orders".to_string(), "orders_tenant7".to_string()), ("users".to_string(), "users_tenant7".to_string())].into();
+
+        assert_eq!(
+            rewrite_tables("SELECT orders.id, 'orders' FROM orders WHERE note = 'from orders' -- orders", &rewrites),
+            "SELECT orders.id, 'orders' FROM `orders_tenant7` WHERE note = 'from orders' -- orders"
+        );
+        assert_eq!(
+            rewrite_tables("SELECT * FROM `orders` o JOIN users u ON u.id = o.user_id", &rewrites),
+            "SELECT * FROM `orders_tenant7` o JOIN `users_tenant7` u ON u.id = o.user_id"
+        );
+        assert_eq!(
+            rewrite_tables("SELECT * FROM users, orders WHERE id IN (SELECT user_id FROM orders)", &rewrites),
+            "SELECT * FROM `users_tenant7`, `orders_tenant7` WHERE id IN (SELECT user_id FROM `orders_tenant7`)"
+        );
+        assert_eq!(
+            rewrite_tables("SELECT * FROM (SELECT id FROM users) AS d, orders", &rewrites),
+            "SELECT * FROM (SELECT id FROM `users_tenant7`) AS d, `orders_tenant7`"
+        );
+
+        // Qualified names and other case are left alone
+        assert_eq!(rewrite_tables("SELECT * FROM shop.orders, Orders", &rewrites), "SELECT * FROM shop.orders, Orders");
+        assert_eq!(rewrite_tables("SELECT a, b FROM t WHERE orders > 1", &rewrites), "SELECT a, b FROM t WHERE orders > 1");
+    }
+
+    #[test]
+    fn test_fingerprint_hides_literals() {
+        assert_eq!(
+            fingerprint("SELECT *  FROM users\n WHERE email = 'ada@example.com' AND id > 42 -- lookup"),
+            "SELECT * FROM users WHERE email = ? AND id > ?"
+        );
+        assert_eq!(fingerprint("SELECT `col 1`, t1.x FROM t1 WHERE y IN (1.5, \"two\")"), "SELECT `col 1`, t1.x FROM t1 WHERE y IN (?, ?)");
+    }
+
+    #[test]
+    fn test_quote_string_literal() {
+        assert_eq!(quote_string_literal("it's"), "'it''s'");
+        assert_eq!(quote_string_literal("a\\b"), "'a\\\\b'");
+    }
+}
@@ -1,10 +1,11 @@
 //! Result streaming functionality
 
 use serde::{Serialize, Deserialize};
-use crate::query::{Row, QueryResult, ColumnInfo};
-use crate::Result;
-use sqlx::{MySqlConnection, Row as SqlxRow, Column, TypeInfo};
-use tracing::{info, debug, error};
+use crate::query::{Row, QueryResult, ColumnInfo, QueryProcessor, ValueConversionOptions};
+use crate::{Result, ServerError};
+use sqlx::{Column, MySqlConnection, Row as SqlxRow};
+use std::time::Duration;
+use tracing::{info, warn};
 use tokio_stream::{Stream, StreamExt};
 
 
@@ -19,27 +20,107 @@ pub struct ResultChunk {
     pub is_final: bool,
     /// Total number of rows (if known)
     pub total_rows: Option<u64>,
+    /// Whether the result was cut off at the configured maximum row count
+    #[serde(default)]
+    pub truncated: bool,
+    /// Columns in which a value was truncated (`max_blob_bytes`, `max_cell_length`,
+    /// `max_json_depth`), reported on the final chunk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated_columns: Vec<String>,
+    /// `[row, column]` positions of the truncated values, with rows counted
+    /// across the whole stream, reported on the final chunk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated_cells: Vec<[usize; 2]>,
+    /// Error that ended the stream early; the rows received so far are incomplete
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the rows were moved into `payload` as gzipped, base64-encoded JSON
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compressed: bool,
+    /// Compressed rows, present when `compressed` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+}
+
+impl ResultChunk {
+    /// Gzip the rows into a base64 `payload`, leaving `rows` empty
+    pub fn compress(&mut self) -> Result<()> {
+        use base64::{Engine as _, engine::general_purpose};
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let json = serde_json::to_vec(&self.rows)
+            .map_err(|e| ServerError::serialization_error(e, "chunk rows".to_string()))?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&json)
+            .and_then(|_| encoder.finish())
+            .map(|gzipped| {
+                self.payload = Some(general_purpose::STANDARD.encode(gzipped));
+                self.compressed = true;
+                self.rows.clear();
+            })
+            .map_err(|e| ServerError::io_error(e, "compressing chunk rows".to_string()))
+    }
+
+    /// Rows of the chunk, decompressing the payload if the chunk is compressed
+    pub fn decompressed_rows(&self) -> Result<Vec<Row>> {
+        use base64::{Engine as _, engine::general_purpose};
+        use flate2::read::GzDecoder;
+
+        let payload = match (self.compressed, &self.payload) {
+            (false, _) => return Ok(self.rows.clone()),
+            (true, Some(payload)) => payload,
+            (true, None) => return Err(ServerError::validation_error(
+                "Compressed chunk has no payload".to_string(),
+                Some(self.chunk_id.to_string())
+            )),
+        };
+
+        let gzipped = general_purpose::STANDARD.decode(payload)
+            .map_err(|e| ServerError::validation_error(
+                "Compressed chunk payload is not valid base64".to_string(),
+                Some(e.to_string())
+            ))?;
+        serde_json::from_reader(GzDecoder::new(gzipped.as_slice()))
+            .map_err(|e| ServerError::serialization_error(e, "decompressing chunk rows".to_string()))
+    }
 }
 
 /// Streaming configuration
+///
+/// By default results are sent in chunks of 100 rows with no cap on the
+/// total number of rows.
 #[derive(Debug, Clone)]
 pub struct StreamingConfig {
-    /// Number of rows per chunk
-    pub chunk_size: usize,
+    /// Number of rows per chunk (flush granularity)
+    pub rows_per_chunk: usize,
+    /// Hard cap on the total number of rows streamed (unlimited if unset)
+    pub max_total_rows: Option<usize>,
     /// Maximum number of chunks to buffer
     pub max_buffer_size: usize,
+    /// Interval between SSE keep-alive comments while no chunk is sent
+    pub keep_alive_interval: Duration,
+    /// Text of the SSE keep-alive comment
+    pub keep_alive_text: String,
+    /// Compress chunks whose rows serialize to more than this many bytes (off if unset)
+    pub compression_threshold_bytes: Option<usize>,
 }
 
 impl Default for StreamingConfig {
     fn default() -> Self {
         Self {
-            chunk_size: 100,
+            rows_per_chunk: 100,
+            max_total_rows: None,
             max_buffer_size: 10,
+            keep_alive_interval: Duration::from_secs(15),
+            keep_alive_text: String::new(),
+            compression_threshold_bytes: None,
         }
     }
 }
 
 /// Result streamer for handling large query results
+#[derive(Clone)]
 pub struct ResultStreamer {
     config: StreamingConfig,
 }
@@ -57,50 +138,63 @@ impl ResultStreamer {
         Self { config }
     }
 
-    /// Execute a streaming SELECT query (simplified version)
+    /// Get the streaming configuration
+    pub fn config(&self) -> &StreamingConfig {
+        &self.config
+    }
+
+    /// Build the SSE keep-alive settings from the streaming configuration
+    pub fn keep_alive(&self) -> warp::sse::KeepAlive {
+        warp::sse::keep_alive()
+            .interval(self.config.keep_alive_interval)
+            .text(self.config.keep_alive_text.clone())
+    }
+
+    /// Execute a streaming SELECT query
+    ///
+    /// Rows are read incrementally and reading stops once `max_total_rows`
+    /// is reached, in which case the chunks are flagged as truncated. If the
+    /// fetch fails after rows were received, the rows read so far are
+    /// returned followed by a terminal error chunk.
     pub async fn execute_streaming_query(
         &self,
         connection: &mut MySqlConnection,
         sql: &str,
+        options: &ValueConversionOptions,
     ) -> Result<Vec<ResultChunk>> {
         info!("Executing streaming query: {}", sql);
         
-        // For now, execute the query normally and then chunk the results
-        // This is a simplified implementation that avoids complex lifetime issues
         use sqlx::Executor;
-        let rows = connection.fetch_all(sql).await?;
-        
-        if rows.is_empty() {
-            return Ok(vec![ResultChunk {
-                chunk_id: 0,
-                rows: vec![],
-                is_final: true,
-                total_rows: Some(0),
-            }]);
-        }
 
-        // Convert all rows to our Row format
+        // Fetch one row beyond the cap so truncation can be detected
+        let fetch_limit = self.config.max_total_rows.map(|max| max.saturating_add(1));
+
+        let mut column_names = Vec::new();
+        let mut truncated_cells = Vec::new();
         let mut converted_rows = Vec::new();
-        for row in rows {
-            let values = Self::convert_row_to_json_values(&row)?;
+        let mut rows = connection.fetch(sql);
+        let mut chunks = loop {
+            let row = match rows.try_next().await {
+                Ok(Some(row)) => row,
+                Ok(None) => break self.build_chunks(converted_rows),
+                Err(e) if !converted_rows.is_empty() => {
+                    warn!("Streaming query failed after {} rows: {}", converted_rows.len(), e);
+                    break self.build_partial_chunks(converted_rows, &e.to_string());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if column_names.is_empty() {
+                column_names = row.columns().iter().map(|c| c.name().to_string()).collect();
+            }
+            let values = QueryProcessor::convert_row_to_json_values(&row, converted_rows.len(), options, &mut truncated_cells)?;
             converted_rows.push(Row { values });
-        }
 
-        let total_rows = converted_rows.len() as u64;
-        let chunk_size = self.config.chunk_size;
-        
-        // Create chunks
-        let mut chunks = Vec::new();
-        for (i, chunk_rows) in converted_rows.chunks(chunk_size).enumerate() {
-            let is_final = (i + 1) * chunk_size >= converted_rows.len();
-            chunks.push(ResultChunk {
-                chunk_id: i as u64,
-                rows: chunk_rows.to_vec(),
-                is_final,
-                total_rows: Some(total_rows),
-            });
-        }
+            if fetch_limit.is_some_and(|limit| converted_rows.len() >= limit) {
+                break self.build_chunks(converted_rows);
+            }
+        };
 
+        self.mark_truncated_cells(&mut chunks, &column_names, truncated_cells);
         Ok(chunks)
     }
 
@@ -109,6 +203,7 @@ impl ResultStreamer {
         &self,
         connection: &mut MySqlConnection,
         sql: &str,
+        options: &ValueConversionOptions,
     ) -> Result<Vec<ResultChunk>> {
         info!("Executing query as stream: {}", sql);
         
@@ -116,194 +211,126 @@ impl ResultStreamer {
         use sqlx::Executor;
         let rows = connection.fetch_all(sql).await?;
         
+        // Convert all rows to our Row format
+        let column_names: Vec<String> = rows.first()
+            .map(|row| row.columns().iter().map(|c| c.name().to_string()).collect())
+            .unwrap_or_default();
+        let mut truncated_cells = Vec::new();
+        let mut converted_rows = Vec::new();
+        for (row_index, row) in rows.iter().enumerate() {
+            let values = QueryProcessor::convert_row_to_json_values(row, row_index, options, &mut truncated_cells)?;
+            converted_rows.push(Row { values });
+        }
+
+        let mut chunks = self.build_chunks(converted_rows);
+        self.mark_truncated_cells(&mut chunks, &column_names, truncated_cells);
+        Ok(chunks)
+    }
+
+    /// Report `truncated_cells`, and the columns they fall in, on the final chunk
+    ///
+    /// Cells in rows dropped by `max_total_rows` are left out.
+    pub fn mark_truncated_cells(&self, chunks: &mut [ResultChunk], column_names: &[String], mut truncated_cells: Vec<[usize; 2]>) {
+        if let Some(max) = self.config.max_total_rows {
+            truncated_cells.retain(|&[row, _]| row < max);
+        }
+        if let Some(last) = chunks.last_mut() {
+            last.truncated_columns = column_names
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| truncated_cells.iter().any(|&[_, column]| column == *index))
+                .map(|(_, name)| name.clone())
+                .collect();
+            last.truncated_cells = truncated_cells;
+        }
+    }
+
+    /// Split rows into chunks of `rows_per_chunk`, applying `max_total_rows`
+    pub fn build_chunks(&self, mut rows: Vec<Row>) -> Vec<ResultChunk> {
+        let truncated = match self.config.max_total_rows {
+            Some(max) if rows.len() > max => {
+                rows.truncate(max);
+                true
+            }
+            _ => false,
+        };
+
         if rows.is_empty() {
-            // Return empty result
-            return Ok(vec![ResultChunk {
+            return vec![ResultChunk {
                 chunk_id: 0,
                 rows: vec![],
                 is_final: true,
                 total_rows: Some(0),
-            }]);
+                truncated,
+                truncated_columns: Vec::new(),
+                truncated_cells: Vec::new(),
+                error: None,
+                compressed: false,
+                payload: None,
+            }];
         }
 
-        // Extract column information from the first row
-        let first_row = &rows[0];
-        let _columns = first_row.columns()
-            .iter()
-            .map(|col| ColumnInfo {
-                name: col.name().to_string(),
-                data_type: col.type_info().name().to_string(),
-                nullable: true,
-            })
-            .collect::<Vec<_>>();
-
-        // Convert all rows to our Row format
-        let mut converted_rows = Vec::new();
-        for row in rows {
-            let values = Self::convert_row_to_json_values(&row)?;
-            converted_rows.push(Row { values });
-        }
+        let total_rows = rows.len() as u64;
+        let rows_per_chunk = self.config.rows_per_chunk.max(1);
 
-        let total_rows = converted_rows.len() as u64;
-        let chunk_size = self.config.chunk_size;
-        
-        // Create chunks
-        let chunks: Vec<ResultChunk> = converted_rows
-            .chunks(chunk_size)
+        rows.chunks(rows_per_chunk)
             .enumerate()
-            .map(|(i, chunk_rows)| {
-                let is_final = (i + 1) * chunk_size >= converted_rows.len();
-                ResultChunk {
-                    chunk_id: i as u64,
-                    rows: chunk_rows.to_vec(),
-                    is_final,
-                    total_rows: Some(total_rows),
-                }
+            .map(|(i, chunk_rows)| ResultChunk {
+                chunk_id: i as u64,
+                rows: chunk_rows.to_vec(),
+                is_final: (i + 1) * rows_per_chunk >= rows.len(),
+                total_rows: Some(total_rows),
+                truncated,
+                truncated_columns: Vec::new(),
+                truncated_cells: Vec::new(),
+                error: None,
+                compressed: false,
+                payload: None,
             })
-            .collect();
-
-        Ok(chunks)
+            .map(|chunk| self.compress_if_large(chunk))
+            .collect()
     }
 
-    /// Convert a MySQL row to JSON values
-    fn convert_row_to_json_values(row: &sqlx::mysql::MySqlRow) -> Result<Vec<serde_json::Value>> {
-        let mut values = Vec::new();
-        
-        for (i, column) in row.columns().iter().enumerate() {
-            let value = Self::convert_mysql_value_to_json(row, i, column)?;
-            values.push(value);
+    /// Compress a chunk when its rows exceed `compression_threshold_bytes`
+    ///
+    /// Small chunks, and chunks that fail to compress, are sent as-is.
+    fn compress_if_large(&self, mut chunk: ResultChunk) -> ResultChunk {
+        let Some(threshold) = self.config.compression_threshold_bytes else {
+            return chunk;
+        };
+
+        let size = serde_json::to_vec(&chunk.rows).map_or(0, |json| json.len());
+        if size > threshold {
+            if let Err(e) = chunk.compress() {
+                warn!("Failed to compress chunk {}: {}", chunk.chunk_id, e);
+            }
         }
-        
-        Ok(values)
+        chunk
     }
 
-    /// Convert a MySQL value to a JSON value
-    fn convert_mysql_value_to_json(
-        row: &sqlx::mysql::MySqlRow,
-        column_index: usize,
-        column: &sqlx::mysql::MySqlColumn,
-    ) -> Result<serde_json::Value> {
-        use sqlx::{Column, TypeInfo, ValueRef};
-        
-        // Check if the value is NULL first
-        if row.try_get_raw(column_index)?.is_null() {
-            return Ok(serde_json::Value::Null);
+    /// Chunk the rows received before a mid-stream failure and append a
+    /// terminal error chunk so clients know the result is incomplete
+    pub fn build_partial_chunks(&self, rows: Vec<Row>, error: &str) -> Vec<ResultChunk> {
+        let received = rows.len();
+        let mut chunks = if rows.is_empty() { Vec::new() } else { self.build_chunks(rows) };
+        for chunk in &mut chunks {
+            chunk.is_final = false;
+            chunk.total_rows = None;
         }
 
-        let type_name = column.type_info().name();
-        
-        match type_name {
-            // Integer types
-            "TINYINT" => {
-                let val: i8 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            "SMALLINT" => {
-                let val: i16 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            "MEDIUMINT" | "INT" => {
-                let val: i32 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            "BIGINT" => {
-                let val: i64 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            
-            // Unsigned integer types
-            "TINYINT UNSIGNED" => {
-                let val: u8 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            "SMALLINT UNSIGNED" => {
-                let val: u16 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            "MEDIUMINT UNSIGNED" | "INT UNSIGNED" => {
-                let val: u32 = row.try_get(column_index)?;
-                Ok(serde_json::Value::Number(val.into()))
-            }
-            "BIGINT UNSIGNED" => {
-                let val: u64 = row.try_get(column_index)?;
-                // JSON numbers are limited to i64 range, so convert large u64 to string
-                if val > i64::MAX as u64 {
-                    Ok(serde_json::Value::String(val.to_string()))
-                } else {
-                    Ok(serde_json::Value::Number((val as i64).into()))
-                }
-            }
-            
-            // Floating point types
-            "FLOAT" => {
-                let val: f32 = row.try_get(column_index)?;
-                if let Some(num) = serde_json::Number::from_f64(val as f64) {
-                    Ok(serde_json::Value::Number(num))
-                } else {
-                    Ok(serde_json::Value::String(val.to_string()))
-                }
-            }
-            "DOUBLE" => {
-                let val: f64 = row.try_get(column_index)?;
-                if let Some(num) = serde_json::Number::from_f64(val) {
-                    Ok(serde_json::Value::Number(num))
-                } else {
-                    Ok(serde_json::Value::String(val.to_string()))
-                }
-            }
-            
-            // String types
-            "CHAR" | "VARCHAR" | "TEXT" | "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT" => {
-                let val: String = row.try_get(column_index)?;
-                Ok(serde_json::Value::String(val))
-            }
-            
-            // Binary types - convert to base64 string
-            "BINARY" | "VARBINARY" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
-                let val: Vec<u8> = row.try_get(column_index)?;
-                use base64::{Engine as _, engine::general_purpose};
-                let base64_str = general_purpose::STANDARD.encode(&val);
-                Ok(serde_json::Value::String(base64_str))
-            }
-            
-            // Date and time types
-            "DATE" => {
-                let val: sqlx::types::chrono::NaiveDate = row.try_get(column_index)?;
-                Ok(serde_json::Value::String(val.to_string()))
-            }
-            "TIME" => {
-                let val: sqlx::types::chrono::NaiveTime = row.try_get(column_index)?;
-                Ok(serde_json::Value::String(val.to_string()))
-            }
-            "DATETIME" | "TIMESTAMP" => {
-                let val: sqlx::types::chrono::NaiveDateTime = row.try_get(column_index)?;
-                Ok(serde_json::Value::String(val.to_string()))
-            }
-            
-            // Boolean type
-            "BOOLEAN" | "BOOL" => {
-                let val: bool = row.try_get(column_index)?;
-                Ok(serde_json::Value::Bool(val))
-            }
-            
-            // JSON type
-            "JSON" => {
-                let val: serde_json::Value = row.try_get(column_index)?;
-                Ok(val)
-            }
-            
-            // Default case - try to get as string
-            _ => {
-                debug!("Unknown MySQL type '{}', attempting string conversion", type_name);
-                match row.try_get::<String, _>(column_index) {
-                    Ok(val) => Ok(serde_json::Value::String(val)),
-                    Err(_) => {
-                        error!("Failed to convert MySQL type '{}' to JSON", type_name);
-                        Ok(serde_json::Value::Null)
-                    }
-                }
-            }
-        }
+        chunks.push(ResultChunk {
+            chunk_id: chunks.len() as u64,
+            rows: vec![],
+            is_final: true,
+            total_rows: None,
+            truncated: false,
+            truncated_columns: Vec::new(),
+            truncated_cells: Vec::new(),
+            error: Some(format!("connection lost after {} rows: {}", received, error)),
+            compressed: false,
+            payload: None,
+        });
+        chunks
     }
 
     /// Collect all chunks from a stream into a single QueryResult
@@ -314,9 +341,13 @@ impl ResultStreamer {
         let mut all_rows = Vec::new();
         let mut columns = Vec::new();
         let mut total_rows = None;
+        let mut truncated_cells = Vec::new();
         
         while let Some(chunk_result) = stream.next().await {
-            let chunk = chunk_result?;
+            let mut chunk = chunk_result?;
+            if chunk.compressed {
+                chunk.rows = chunk.decompressed_rows()?;
+            }
             
             // Extract columns from first chunk (if we haven't already)
             if columns.is_empty() && !chunk.rows.is_empty() {
@@ -328,6 +359,8 @@ impl ResultStreamer {
                         name: format!("column_{}", i),
                         data_type: "UNKNOWN".to_string(),
                         nullable: true,
+                        encoding: None,
+                        truncated: false,
                     });
                 }
             }
@@ -336,6 +369,7 @@ impl ResultStreamer {
             
             if chunk.is_final {
                 total_rows = chunk.total_rows;
+                truncated_cells = chunk.truncated_cells;
                 break;
             }
         }
@@ -345,6 +379,7 @@ impl ResultStreamer {
             rows: all_rows,
             affected_rows: total_rows,
             execution_time_ms,
+            truncated_cells,
         })
     }
 }
@@ -387,13 +422,25 @@ mod tests {
             chunk_id in any::<u64>(),
             rows in prop::collection::vec(arb_row(), 0..50),
             is_final in any::<bool>(),
-            total_rows in prop::option::of(any::<u64>())
+            total_rows in prop::option::of(any::<u64>()),
+            truncated in any::<bool>(),
+            truncated_columns in prop::collection::vec("[a-z_]+", 0..3),
+            truncated_cells in prop::collection::vec(any::<[usize; 2]>(), 0..3),
+            error in prop::option::of("[\\PC]*"),
+            compressed in any::<bool>(),
+            payload in prop::option::of("[A-Za-z0-9+/=]*")
         ) -> ResultChunk {
             ResultChunk {
                 chunk_id,
                 rows,
                 is_final,
                 total_rows,
+                truncated,
+                truncated_columns,
+                truncated_cells,
+                error,
+                compressed,
+                payload,
             }
         }
     }
@@ -417,4 +464,148 @@ mod tests {
             prop_assert_eq!(result_chunk, deserialized);
         }
     }
-}
\ No newline at end of file
+
+    fn numbered_rows(count: usize) -> Vec<Row> {
+        (0..count).map(|i| Row { values: vec![serde_json::json!(i)] }).collect()
+    }
+
+    #[test]
+    fn test_build_chunks_uses_rows_per_chunk() {
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            rows_per_chunk: 4,
+            ..StreamingConfig::default()
+        });
+
+        let chunks = streamer.build_chunks(numbered_rows(10));
+
+        let sizes: Vec<usize> = chunks.iter().map(|c| c.rows.len()).collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+        assert!(chunks.last().unwrap().is_final);
+        assert!(chunks.iter().all(|c| c.total_rows == Some(10) && !c.truncated));
+    }
+
+    #[test]
+    fn test_build_chunks_applies_max_total_rows() {
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            rows_per_chunk: 4,
+            max_total_rows: Some(6),
+            ..StreamingConfig::default()
+        });
+
+        let chunks = streamer.build_chunks(numbered_rows(10));
+
+        let sizes: Vec<usize> = chunks.iter().map(|c| c.rows.len()).collect();
+        assert_eq!(sizes, vec![4, 2]);
+        assert!(chunks.iter().all(|c| c.total_rows == Some(6) && c.truncated));
+
+        // Exactly at the cap is not a truncation
+        let chunks = streamer.build_chunks(numbered_rows(6));
+        assert!(chunks.iter().all(|c| !c.truncated));
+    }
+
+    #[test]
+    fn test_mid_stream_failure_ends_with_error_chunk() {
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            rows_per_chunk: 4,
+            ..StreamingConfig::default()
+        });
+
+        let chunks = streamer.build_partial_chunks(numbered_rows(6), "Broken pipe");
+
+        let sizes: Vec<usize> = chunks.iter().map(|c| c.rows.len()).collect();
+        assert_eq!(sizes, vec![4, 2, 0]);
+        assert!(chunks[..2].iter().all(|c| !c.is_final && c.error.is_none() && c.total_rows.is_none()));
+
+        let last = chunks.last().unwrap();
+        assert!(last.is_final);
+        assert_eq!(last.chunk_id, 2);
+        assert_eq!(last.error.as_deref(), Some("connection lost after 6 rows: Broken pipe"));
+    }
+
+    #[test]
+    fn test_truncated_columns_reported_on_final_chunk() {
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            rows_per_chunk: 2,
+            ..StreamingConfig::default()
+        });
+        let names = ["id".to_string(), "body".to_string(), "blob".to_string()];
+
+        let mut chunks = streamer.build_chunks(numbered_rows(3));
+        streamer.mark_truncated_cells(&mut chunks, &names, vec![[0, 2], [2, 1]]);
+
+        assert!(chunks[0].truncated_columns.is_empty() && chunks[0].truncated_cells.is_empty());
+        assert_eq!(chunks[1].truncated_columns, ["body", "blob"]);
+        assert_eq!(chunks[1].truncated_cells, [[0, 2], [2, 1]]);
+        assert!(serde_json::to_value(&chunks[0]).unwrap().get("truncated_columns").is_none());
+
+        let mut untouched = streamer.build_chunks(numbered_rows(1));
+        streamer.mark_truncated_cells(&mut untouched, &names, Vec::new());
+        assert!(untouched[0].truncated_columns.is_empty());
+        assert!(serde_json::to_value(&untouched[0]).unwrap().get("truncated_cells").is_none());
+    }
+
+    #[test]
+    fn test_truncated_cells_beyond_max_total_rows_are_dropped() {
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            max_total_rows: Some(2),
+            ..StreamingConfig::default()
+        });
+        let names = ["id".to_string(), "body".to_string()];
+
+        // The row fetched past the cap to detect truncation is not sent
+        let mut chunks = streamer.build_chunks(numbered_rows(3));
+        streamer.mark_truncated_cells(&mut chunks, &names, vec![[1, 0], [2, 1]]);
+
+        assert_eq!(chunks[0].truncated_cells, [[1, 0]]);
+        assert_eq!(chunks[0].truncated_columns, ["id"]);
+    }
+
+    #[test]
+    fn test_large_chunks_are_compressed_and_round_trip() {
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            rows_per_chunk: 2,
+            compression_threshold_bytes: Some(1024),
+            ..StreamingConfig::default()
+        });
+        let large = Row { values: vec![serde_json::json!("x".repeat(4096))] };
+        let small = Row { values: vec![serde_json::json!(1)] };
+
+        let chunks = streamer.build_chunks(vec![large.clone(), large.clone(), small.clone()]);
+
+        assert!(chunks[0].compressed);
+        assert!(chunks[0].rows.is_empty());
+        assert!(chunks[0].payload.as_ref().unwrap().len() < 4096);
+        assert!(!chunks[1].compressed && chunks[1].payload.is_none());
+
+        // The serialized chunk decompresses back to the original rows
+        let received: ResultChunk = serde_json::from_str(&serde_json::to_string(&chunks[0]).unwrap()).unwrap();
+        assert_eq!(received.decompressed_rows().unwrap(), vec![large.clone(), large]);
+        assert_eq!(chunks[1].decompressed_rows().unwrap(), vec![small]);
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_uses_configured_interval_and_text() {
+        use warp::hyper::body::HttpBody;
+        use warp::Reply;
+
+        let streamer = ResultStreamer::with_config(StreamingConfig {
+            keep_alive_interval: Duration::from_millis(20),
+            keep_alive_text: "still-running".to_string(),
+            ..StreamingConfig::default()
+        });
+
+        // A stream that never yields, as with a slow query
+        let events = futures::stream::pending::<std::result::Result<warp::sse::Event, warp::Error>>();
+        let mut body = warp::sse::reply(streamer.keep_alive().stream(events))
+            .into_response()
+            .into_body();
+
+        let data = tokio::time::timeout(Duration::from_secs(1), body.data())
+            .await
+            .expect("keep-alive should be sent within the configured interval")
+            .expect("body should yield a keep-alive frame")
+            .expect("keep-alive frame should not be an error");
+
+        assert_eq!(String::from_utf8_lossy(&data), ":still-running\n\n");
+    }
+}
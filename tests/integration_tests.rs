@@ -2,9 +2,12 @@
 //! 
 //! Tests end-to-end query execution through MCP and streaming functionality with real database
 
-use mysql_mcp_server::{ConnectionConfig, McpServer};
-use mysql_mcp_server::query::QueryRequest;
+use mysql_mcp_server::{Config, ConnectionConfig, McpConfig, McpServer, ServerConfig};
+use mysql_mcp_server::connection::ConnectionManager;
+use mysql_mcp_server::query::{QueryProcessor, QueryRequest, ValueConversionOptions};
+use mysql_mcp_server::streaming::ResultStreamer;
 use serde_json::json;
+use std::collections::HashMap;
 use std::env;
 use tokio::time::{timeout, Duration};
 
@@ -28,6 +31,7 @@ async fn create_test_server() -> Option<McpServer> {
     let config = TestConfig::from_env()?;
     let connection_config = ConnectionConfig {
         database_url: config.database_url,
+        ..Default::default()
     };
     
     let server = McpServer::new(connection_config);
@@ -491,4 +495,289 @@ async fn test_error_handling_integration() {
     
     let result = server.handle_call_tool(Some(invalid_json_params)).await;
     assert!(result.is_err(), "Invalid argument types should return error");
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_blob_columns_are_base64_encoded() {
+    let config = match TestConfig::from_env() {
+        Some(config) => config,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let mut manager = ConnectionManager::new(ConnectionConfig {
+        database_url: config.database_url,
+        ..Default::default()
+    });
+    manager.connect().await.expect("Failed to connect to test database");
+    let connection = manager.connection_mut().expect("Connection should be established");
+
+    // Temporary tables are scoped to this connection and dropped automatically
+    for sql in [
+        "CREATE TEMPORARY TABLE test_blobs (id INT PRIMARY KEY, payload BLOB)",
+        "INSERT INTO test_blobs (id, payload) VALUES (1, 'hello world')",
+    ] {
+        let request = QueryRequest { sql: sql.to_string(), parameters: None, stream_results: false };
+        QueryProcessor::execute_query(connection, &request).await.expect("Setup query should succeed");
+    }
+
+    let request = QueryRequest {
+        sql: "SELECT id, payload FROM test_blobs".to_string(),
+        parameters: None,
+        stream_results: false,
+    };
+    let options = ValueConversionOptions { max_blob_bytes: Some(5), ..Default::default() };
+    let result = QueryProcessor::execute_query_with_options(connection, &request, &options)
+        .await
+        .expect("BLOB query should succeed");
+
+    assert_eq!(result.columns[1].encoding.as_deref(), Some("base64"));
+    assert!(result.columns[1].truncated);
+    assert_eq!(result.rows[0].values[1], json!("aGVsbG8="));
+
+    let _ = manager.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_long_text_cells_are_truncated() {
+    let config = match TestConfig::from_env() {
+        Some(config) => config,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let mut manager = ConnectionManager::new(ConnectionConfig {
+        database_url: config.database_url,
+        ..Default::default()
+    });
+    manager.connect().await.expect("Failed to connect to test database");
+    let connection = manager.connection_mut().expect("Connection should be established");
+
+    let request = QueryRequest {
+        sql: "SELECT REPEAT('x', 100) AS big, 'tiny' AS small, CAST(12345678901.25 AS DECIMAL(20,2)) AS amount \
+              UNION ALL SELECT 'short', REPEAT('y', 20), CAST(1 AS DECIMAL(20,2))".to_string(),
+        parameters: None,
+        stream_results: false,
+    };
+    let options = ValueConversionOptions { max_cell_length: Some(10), ..Default::default() };
+    let result = QueryProcessor::execute_query_with_options(connection, &request, &options)
+        .await
+        .expect("Query should succeed");
+
+    assert!(result.columns[0].truncated);
+    assert_eq!(result.rows[0].values[0], json!("xxxxxxxxxx…(truncated 90 bytes)"));
+    assert!(result.columns[1].truncated);
+    assert_eq!(result.rows[0].values[1], json!("tiny"));
+    assert_eq!(result.rows[1].values[1], json!("yyyyyyyyyy…(truncated 10 bytes)"));
+    assert!(!result.columns[2].truncated);
+    assert_eq!(result.rows[0].values[2], json!("12345678901.25"));
+    // Only the long values are flagged, not every cell of their columns
+    assert_eq!(result.truncated_cells, [[0, 0], [1, 1]]);
+
+    let chunks = ResultStreamer::new()
+        .execute_streaming_query(connection, &request.sql, &options)
+        .await
+        .expect("Streaming query should succeed");
+    let last = chunks.last().expect("Streaming returns at least one chunk");
+    assert_eq!(last.truncated_columns, ["big", "small"]);
+    assert_eq!(last.truncated_cells, [[0, 0], [1, 1]]);
+    assert_eq!(chunks[0].rows[0].values[0], json!("xxxxxxxxxx…(truncated 90 bytes)"));
+    assert_eq!(chunks[0].rows[0].values[2], json!("12345678901.25"));
+
+    let _ = manager.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_validate_syntax_prepares_and_deallocates() {
+    let config = match TestConfig::from_env() {
+        Some(config) => config,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let mut manager = ConnectionManager::new(ConnectionConfig {
+        database_url: config.database_url,
+        ..Default::default()
+    });
+    manager.connect().await.expect("Failed to connect to test database");
+    let connection = manager.connection_mut().expect("Connection should be established");
+
+    let valid = QueryProcessor::validate_syntax(connection, "SELECT ? + ?, 'literal ?'")
+        .await
+        .expect("Validation should run");
+    assert!(valid.valid);
+    assert_eq!(valid.param_count, 2);
+
+    let invalid = QueryProcessor::validate_syntax(connection, "SELCT 1")
+        .await
+        .expect("Validation should run");
+    assert!(!invalid.valid);
+    assert!(invalid.error.is_some());
+
+    // The validation statement must not be left prepared on either path
+    let execute = QueryRequest {
+        sql: "EXECUTE mcp_validate_sql".to_string(),
+        parameters: None,
+        stream_results: false,
+    };
+    assert!(QueryProcessor::execute_query(connection, &execute).await.is_err());
+
+    let _ = manager.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_validate_sql_checks_rewritten_tables() {
+    let config = match TestConfig::from_env() {
+        Some(config) => config,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let mut setup = ConnectionManager::new(ConnectionConfig {
+        database_url: config.database_url.clone(),
+        ..Default::default()
+    });
+    setup.connect().await.expect("Failed to connect to test database");
+    let create = QueryRequest {
+        sql: "CREATE TABLE IF NOT EXISTS mcp_rewrite_target (id INT)".to_string(),
+        parameters: None,
+        stream_results: false,
+    };
+    QueryProcessor::execute_query(setup.connection_mut().unwrap(), &create).await.expect("Setup query should succeed");
+
+    let mut full_config = Config {
+        server: ServerConfig { port: 0, log_level: "info".to_string() },
+        database: Config::parse_database_url(&config.database_url).expect("TEST_DATABASE_URL should parse"),
+        mcp: McpConfig::default(),
+    };
+    full_config.mcp.table_rewrites = HashMap::from([
+        ("mcp_rewrite_alias".to_string(), "mcp_rewrite_target".to_string()),
+        ("mcp_rewrite_ghost".to_string(), "mcp_rewrite_missing".to_string()),
+    ]);
+    let server = McpServer::from_config(&full_config);
+    server.initialize().await.expect("Server should connect");
+
+    // The source table does not exist; only the rewritten statement is valid
+    let valid = server.handle_validate_sql(json!({"sql": "SELECT id FROM mcp_rewrite_alias"})).await.unwrap();
+    assert_eq!(valid["valid"], true, "unexpected result: {}", valid);
+    assert_eq!(valid["validated_sql"], "SELECT id FROM `mcp_rewrite_target`");
+
+    let missing = server.handle_validate_sql(json!({"sql": "SELECT id FROM mcp_rewrite_ghost"})).await.unwrap();
+    assert_eq!(missing["valid"], false);
+    assert!(missing["error"].as_str().unwrap().contains("mcp_rewrite_missing"));
+
+    let drop = QueryRequest {
+        sql: "DROP TABLE mcp_rewrite_target".to_string(),
+        parameters: None,
+        stream_results: false,
+    };
+    let _ = QueryProcessor::execute_query(setup.connection_mut().unwrap(), &drop).await;
+    let _ = setup.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_zero_dates_do_not_fail_the_query() {
+    let config = match TestConfig::from_env() {
+        Some(config) => config,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let mut manager = ConnectionManager::new(ConnectionConfig {
+        database_url: config.database_url,
+        ..Default::default()
+    });
+    manager.connect().await.expect("Failed to connect to test database");
+    let connection = manager.connection_mut().expect("Connection should be established");
+
+    // Zero dates are only accepted without NO_ZERO_DATE in the SQL mode
+    for sql in [
+        "SET SESSION sql_mode = ''",
+        "CREATE TEMPORARY TABLE test_zero_dates (id INT PRIMARY KEY, d DATE, dt DATETIME)",
+        "INSERT INTO test_zero_dates (id, d, dt) VALUES (1, '0000-00-00', '0000-00-00 00:00:00'), (2, '2024-01-31', NULL)",
+    ] {
+        let request = QueryRequest { sql: sql.to_string(), parameters: None, stream_results: false };
+        QueryProcessor::execute_query(connection, &request).await.expect("Setup query should succeed");
+    }
+
+    let request = QueryRequest {
+        sql: "SELECT d, dt FROM test_zero_dates ORDER BY id".to_string(),
+        parameters: None,
+        stream_results: false,
+    };
+
+    let result = QueryProcessor::execute_query(connection, &request)
+        .await
+        .expect("Zero dates should not fail the query");
+    assert_eq!(result.rows[0].values, vec![json!(null), json!(null)]);
+    assert_eq!(result.rows[1].values[0], json!("2024-01-31"));
+
+    let options = ValueConversionOptions { zero_date_as_null: false, ..Default::default() };
+    let result = QueryProcessor::execute_query_with_options(connection, &request, &options)
+        .await
+        .expect("Zero dates should not fail the query");
+    assert_eq!(result.rows[0].values, vec![json!("0000-00-00"), json!("0000-00-00 00:00:00")]);
+
+    let _ = manager.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_schema_only_returns_columns_without_rows() {
+    let server = match create_test_server().await {
+        Some(server) => server,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let result = server
+        .handle_execute_query(json!({
+            "sql": "SELECT 1 AS id, 'Ada' AS name, NOW() AS created",
+            "schema_only": true
+        }))
+        .await
+        .expect("schema_only query should succeed");
+
+    let names: Vec<&str> = result["columns"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|column| column["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["id", "name", "created"]);
+    assert_eq!(result["rows"], json!([]));
+    assert_eq!(result["schema_only"], true);
+}
+
+#[tokio::test]
+async fn test_database_config_connects_to_valid_config() {
+    let config = match TestConfig::from_env() {
+        Some(config) => config,
+        None => {
+            println!("Skipping integration test - TEST_DATABASE_URL not set");
+            return;
+        }
+    };
+
+    let database = Config::parse_database_url(&config.database_url).expect("TEST_DATABASE_URL should parse");
+    let server = McpServer::new(ConnectionConfig::default());
+
+    let result = server
+        .handle_test_database_config(serde_json::to_value(&database).unwrap())
+        .await
+        .expect("Testing a config should not fail");
+
+    assert_eq!(result["success"], true, "unexpected result: {}", result);
+    assert!(result["server_version"].is_string());
+}